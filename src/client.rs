@@ -0,0 +1,341 @@
+//! A handle carrying everything needed to talk to a Gluster cluster.
+//!
+//! The volume, peer, quota, heal and bitrot operations in this crate are all
+//! methods on GlusterClient.  The free functions of the same name are thin
+//! wrappers that build a default client for every call.
+use std::path::PathBuf;
+use std::process::Output;
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use super::executor::{self, CommandExecutor, LocalExecutor};
+use super::host::{Resolver, SystemResolver};
+use super::retry::RetryPolicy;
+use super::version::GlusterVersion;
+use super::{process_output, GlusterError};
+
+/// The socket the quota daemon listens on
+pub const DEFAULT_QUOTAD_SOCKET: &str = "/var/run/gluster/quotad.socket";
+
+/// Talks to Gluster through the CLI and RPC sockets.
+/// Create one with GlusterClient::builder()
+#[derive(Clone)]
+pub struct GlusterClient {
+    executor: Arc<dyn CommandExecutor>,
+    remote_host: Option<String>,
+    glusterd_socket: Option<PathBuf>,
+    quotad_socket: PathBuf,
+    timeout: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    resolver: Arc<dyn Resolver>,
+    pub(crate) version: Arc<OnceLock<GlusterVersion>>,
+}
+
+impl GlusterClient {
+    /// Start building a new client
+    pub fn builder() -> GlusterClientBuilder {
+        GlusterClientBuilder::new()
+    }
+
+    /// The host the CLI is pointed at with --remote-host, if any
+    pub fn remote_host(&self) -> Option<&str> {
+        self.remote_host.as_deref()
+    }
+
+    /// The path to the quota daemon socket used for RPC quota queries
+    pub fn quotad_socket(&self) -> &PathBuf {
+        &self.quotad_socket
+    }
+
+    /// How long a command may run before it is killed, if limited
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// A copy of this client that kills commands running longer than
+    /// `timeout`.  Use this to limit a single call:
+    /// `client.with_timeout(Duration::from_secs(30)).quota_list("test")`
+    pub fn with_timeout(&self, timeout: Duration) -> GlusterClient {
+        let mut client = self.clone();
+        client.timeout = Some(timeout);
+        client
+    }
+
+    /// How commands that change the cluster are retried, if at all
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
+
+    /// Looks up the addresses of peer and brick hosts
+    pub fn resolver(&self) -> &dyn Resolver {
+        &*self.resolver
+    }
+
+    /// Run the gluster CLI and turn a non-zero exit into
+    /// GlusterError::CommandFailed
+    pub(crate) fn gluster_checked<T: AsRef<str>>(
+        &self,
+        arg_list: &[T],
+    ) -> Result<Output, GlusterError> {
+        let args = self.gluster_args(arg_list);
+        let output = self
+            .executor
+            .execute("gluster", &args, true, self.timeout)?;
+        process_output(self.gluster_argv(arg_list), output)
+    }
+
+    /// The command line gluster_checked runs for arg_list, as errors
+    /// report it
+    pub(crate) fn gluster_argv<T: AsRef<str>>(&self, arg_list: &[T]) -> Vec<String> {
+        let mut argv = vec!["gluster".to_string()];
+        argv.extend(self.gluster_args(arg_list));
+        argv
+    }
+
+    /// Run a gluster CLI command that changes the cluster.  Failures the
+    /// retry policy considers transient are retried after a backoff.
+    pub(crate) fn gluster_mutate<T: AsRef<str>>(
+        &self,
+        arg_list: &[T],
+    ) -> Result<Output, GlusterError> {
+        let policy = match self.retry_policy {
+            Some(ref policy) => policy,
+            None => return self.gluster_checked(arg_list),
+        };
+        let mut attempt = 1;
+        loop {
+            match self.gluster_checked(arg_list) {
+                Err(ref e) if attempt < policy.attempts() && policy.is_retryable(e) => {
+                    let delay = policy.delay(attempt);
+                    warn!(
+                        "Attempt {} of {} failed: {}.  Retrying in {:?}",
+                        attempt,
+                        policy.attempts(),
+                        e,
+                        delay
+                    );
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn gluster_args<T: AsRef<str>>(&self, arg_list: &[T]) -> Vec<String> {
+        let mut args: Vec<String> = vec!["--mode=script".to_string()];
+        if let Some(ref host) = self.remote_host {
+            args.push(format!("--remote-host={}", host));
+        }
+        if let Some(ref socket) = self.glusterd_socket {
+            args.push(format!("--glusterd-sock={}", socket.display()));
+        }
+        args.extend(arg_list.iter().map(|a| a.as_ref().to_string()));
+        args
+    }
+}
+
+impl Default for GlusterClient {
+    /// A client that runs `sudo gluster` locally, or whatever executor was
+    /// installed with executor::set_executor()
+    fn default() -> GlusterClient {
+        GlusterClientBuilder::new().build()
+    }
+}
+
+/// Builds a GlusterClient.  Anything left unset keeps the library defaults:
+/// `gluster` on the PATH run through sudo against the local glusterd.
+#[derive(Clone, Default)]
+pub struct GlusterClientBuilder {
+    executor: Option<Arc<dyn CommandExecutor>>,
+    gluster_binary: Option<PathBuf>,
+    sudo: Option<bool>,
+    remote_host: Option<String>,
+    glusterd_socket: Option<PathBuf>,
+    quotad_socket: Option<PathBuf>,
+    timeout: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    resolver: Option<Arc<dyn Resolver>>,
+}
+
+impl GlusterClientBuilder {
+    pub fn new() -> GlusterClientBuilder {
+        GlusterClientBuilder::default()
+    }
+
+    /// Run commands through a custom executor.  This takes precedence over
+    /// gluster_binary() and sudo().
+    pub fn executor(mut self, executor: Arc<dyn CommandExecutor>) -> GlusterClientBuilder {
+        self.executor = Some(executor);
+        self
+    }
+
+    /// Run the gluster CLI from this path instead of looking it up on the PATH
+    pub fn gluster_binary<P: Into<PathBuf>>(mut self, path: P) -> GlusterClientBuilder {
+        self.gluster_binary = Some(path.into());
+        self
+    }
+
+    /// Whether commands that need root should be run through sudo.
+    /// Default: true
+    pub fn sudo(mut self, sudo: bool) -> GlusterClientBuilder {
+        self.sudo = Some(sudo);
+        self
+    }
+
+    /// Send CLI requests to the glusterd on this host instead of the local one
+    pub fn remote_host<S: Into<String>>(mut self, host: S) -> GlusterClientBuilder {
+        self.remote_host = Some(host.into());
+        self
+    }
+
+    /// Talk to a glusterd listening on a non-default socket
+    pub fn glusterd_socket<P: Into<PathBuf>>(mut self, path: P) -> GlusterClientBuilder {
+        self.glusterd_socket = Some(path.into());
+        self
+    }
+
+    /// Use this quota daemon socket for RPC quota queries.
+    /// Default: /var/run/gluster/quotad.socket
+    pub fn quotad_socket<P: Into<PathBuf>>(mut self, path: P) -> GlusterClientBuilder {
+        self.quotad_socket = Some(path.into());
+        self
+    }
+
+    /// Kill any command that runs longer than this.  Default: no limit
+    pub fn timeout(mut self, timeout: Duration) -> GlusterClientBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry commands that change the cluster when they fail for one of the
+    /// reasons in `policy`.  Default: no retries
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> GlusterClientBuilder {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Look up host names with this resolver.  Default: the system resolver
+    pub fn resolver(mut self, resolver: Arc<dyn Resolver>) -> GlusterClientBuilder {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    pub fn build(self) -> GlusterClient {
+        let executor = match self.executor {
+            Some(executor) => executor,
+            None => match (self.gluster_binary, self.sudo) {
+                (Some(path), sudo) => Arc::new(LocalExecutor::with_gluster_binary(
+                    path,
+                    sudo.unwrap_or(true),
+                )),
+                (None, Some(false)) => Arc::new(LocalExecutor::without_sudo()),
+                (None, Some(true)) => Arc::new(LocalExecutor::new()),
+                (None, None) => executor::executor(),
+            },
+        };
+        GlusterClient {
+            executor,
+            remote_host: self.remote_host,
+            glusterd_socket: self.glusterd_socket,
+            quotad_socket: self
+                .quotad_socket
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_QUOTAD_SOCKET)),
+            timeout: self.timeout,
+            retry_policy: self.retry_policy,
+            resolver: self.resolver.unwrap_or_else(|| Arc::new(SystemResolver)),
+            version: Arc::new(OnceLock::new()),
+        }
+    }
+}
+
+#[test]
+fn test_gluster_client_args() {
+    use executor::CannedExecutor;
+
+    let canned = Arc::new(CannedExecutor::new());
+    let client = GlusterClient::builder()
+        .executor(canned.clone())
+        .remote_host("10.0.0.5")
+        .glusterd_socket("/run/glusterd.socket")
+        .build();
+    client.gluster_checked(&["volume", "list"]).unwrap();
+
+    assert_eq!(
+        canned.calls(),
+        vec![vec![
+            "gluster",
+            "--mode=script",
+            "--remote-host=10.0.0.5",
+            "--glusterd-sock=/run/glusterd.socket",
+            "volume",
+            "list",
+        ],]
+    );
+    assert_eq!(
+        client.quotad_socket(),
+        &PathBuf::from("/var/run/gluster/quotad.socket")
+    );
+    assert_eq!(client.timeout(), None);
+    assert_eq!(
+        client.with_timeout(Duration::from_secs(5)).timeout(),
+        Some(Duration::from_secs(5))
+    );
+}
+
+#[test]
+fn test_gluster_client_retry() {
+    use executor::CannedExecutor;
+    use retry::RetryOn;
+
+    let canned = Arc::new(
+        CannedExecutor::new()
+            .respond(
+                &["volume", "set"],
+                1,
+                "",
+                "volume set: failed: Another transaction is in progress for test. \
+                 Please try again after some time.\n",
+            )
+            .respond(
+                &["volume", "start"],
+                1,
+                "",
+                "volume start: test: failed: Volume test already started\n",
+            ),
+    );
+    let policy = RetryPolicy::new()
+        .max_attempts(3)
+        .initial_backoff(Duration::from_millis(1));
+    let client = GlusterClient::builder()
+        .executor(canned.clone())
+        .retry_policy(policy.clone())
+        .build();
+
+    // Lock contention is retried until the attempts run out
+    assert!(client
+        .gluster_mutate(&["volume", "set", "test", "nfs.disable", "on"])
+        .is_err());
+    assert_eq!(canned.calls().len(), 3);
+
+    // Anything else fails straight away
+    assert!(client.gluster_mutate(&["volume", "start", "test"]).is_err());
+    assert_eq!(canned.calls().len(), 4);
+
+    // Without a policy nothing is retried
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    assert!(client
+        .gluster_mutate(&["volume", "set", "test", "nfs.disable", "on"])
+        .is_err());
+    assert_eq!(canned.calls().len(), 5);
+
+    let client = GlusterClient::builder()
+        .executor(canned.clone())
+        .retry_policy(policy.retry_on(vec![RetryOn::QuorumNotMet]))
+        .build();
+    assert!(client
+        .gluster_mutate(&["volume", "set", "test", "nfs.disable", "on"])
+        .is_err());
+    assert_eq!(canned.calls().len(), 6);
+}
@@ -0,0 +1,367 @@
+//! Pluggable execution of the commands this library shells out to.
+//!
+//! Every CLI wrapper in this crate funnels through a CommandExecutor.  By
+//! default that is a LocalExecutor which runs `sudo gluster ...` on this
+//! machine.  Swap it out with set_executor() to run without sudo, point at a
+//! gluster binary that isn't on the PATH or feed canned output back in tests.
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::GlusterError;
+
+/// How long a timed out command gets to exit after SIGTERM before it is
+/// killed outright
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// Runs a command on behalf of the library and hands back its output.
+pub trait CommandExecutor: Send + Sync {
+    /// Run `command` with `args`.  `as_root` is set for commands that need
+    /// root privileges such as the gluster CLI.  If `timeout` is set the
+    /// command should be stopped once it runs for longer than that.
+    /// # Failures
+    /// Returns GlusterError if the command could not be run at all or
+    /// GlusterError::Timeout if it ran out of time.  A command that runs and
+    /// exits non-zero is not a failure here.
+    fn execute(
+        &self,
+        command: &str,
+        args: &[String],
+        as_root: bool,
+        timeout: Option<Duration>,
+    ) -> Result<Output, GlusterError>;
+}
+
+/// Runs commands as child processes on the local machine
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LocalExecutor {
+    sudo: bool,
+    gluster_binary: Option<PathBuf>,
+}
+
+impl LocalExecutor {
+    /// Runs commands that need root through sudo.  This is the default.
+    pub fn new() -> LocalExecutor {
+        LocalExecutor {
+            sudo: true,
+            gluster_binary: None,
+        }
+    }
+
+    /// Runs every command directly.  Use this when already running as root
+    /// or on hosts where sudo isn't available.
+    pub fn without_sudo() -> LocalExecutor {
+        LocalExecutor {
+            sudo: false,
+            gluster_binary: None,
+        }
+    }
+
+    /// Runs the gluster CLI from `path` instead of looking it up on the PATH
+    pub fn with_gluster_binary<P: Into<PathBuf>>(path: P, sudo: bool) -> LocalExecutor {
+        LocalExecutor {
+            sudo,
+            gluster_binary: Some(path.into()),
+        }
+    }
+
+    fn command(&self, command: &str, args: &[String], as_root: bool) -> Command {
+        let program = match (command, &self.gluster_binary) {
+            ("gluster", Some(path)) => path.clone(),
+            _ => PathBuf::from(command),
+        };
+        let mut cmd = if as_root && self.sudo {
+            let mut cmd = Command::new("sudo");
+            cmd.arg(program);
+            cmd
+        } else {
+            Command::new(program)
+        };
+        cmd.args(args);
+        cmd
+    }
+}
+
+impl Default for LocalExecutor {
+    fn default() -> LocalExecutor {
+        LocalExecutor::new()
+    }
+}
+
+impl CommandExecutor for LocalExecutor {
+    fn execute(
+        &self,
+        command: &str,
+        args: &[String],
+        as_root: bool,
+        timeout: Option<Duration>,
+    ) -> Result<Output, GlusterError> {
+        let mut cmd = self.command(command, args, as_root);
+        debug!("About to run command: {:?}", cmd);
+        match timeout {
+            Some(timeout) => run_with_timeout(cmd, timeout),
+            None => Ok(cmd.output()?),
+        }
+    }
+}
+
+// Runs the command in its own process group so that sudo and whatever it
+// started can be signalled together once the deadline passes.  The child is
+// always waited on before returning so it never lingers as a zombie.  A
+// command run through sudo may outlive the timeout though, see
+// kill_process_group.
+fn run_with_timeout(mut cmd: Command, timeout: Duration) -> Result<Output, GlusterError> {
+    let argv: Vec<String> = ::std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0);
+    let mut child = cmd.spawn()?;
+
+    // Drain the pipes on other threads so a chatty child can't fill them up
+    // and block forever
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            kill_process_group(&mut child);
+            return Err(GlusterError::Timeout { argv, timeout });
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout
+            .map(|t| t.join().unwrap_or_default())
+            .unwrap_or_default(),
+        stderr: stderr
+            .map(|t| t.join().unwrap_or_default())
+            .unwrap_or_default(),
+    })
+}
+
+fn drain<R: Read + Send + 'static>(mut pipe: R) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+// Ask the process group to exit, then kill it if it hasn't by the end of
+// the grace period.  sudo passes SIGTERM on to the command it is running.
+// The SIGKILL can't do the same: it only reaches processes this user may
+// signal, which is sudo but not the root owned command sudo started.  If
+// that command ignores SIGTERM it is left running as an orphan once sudo
+// is killed.
+fn kill_process_group(child: &mut Child) {
+    let pgid = child.id() as libc::pid_t;
+    unsafe {
+        libc::kill(-pgid, libc::SIGTERM);
+    }
+    let grace_deadline = Instant::now() + KILL_GRACE_PERIOD;
+    while Instant::now() < grace_deadline {
+        match child.try_wait() {
+            Ok(Some(_)) => return,
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(_) => break,
+        }
+    }
+    unsafe {
+        libc::kill(-pgid, libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+static EXECUTOR: RwLock<Option<Arc<dyn CommandExecutor>>> = RwLock::new(None);
+
+/// Replace the executor used by every CLI wrapper in this library
+pub fn set_executor(executor: Arc<dyn CommandExecutor>) {
+    let mut current = EXECUTOR.write().unwrap_or_else(|e| e.into_inner());
+    *current = Some(executor);
+}
+
+/// The executor currently in use.  Falls back to LocalExecutor::new() if
+/// set_executor() was never called.
+pub fn executor() -> Arc<dyn CommandExecutor> {
+    let current = EXECUTOR.read().unwrap_or_else(|e| e.into_inner());
+    match *current {
+        Some(ref executor) => Arc::clone(executor),
+        None => Arc::new(LocalExecutor::new()),
+    }
+}
+
+#[test]
+fn test_local_executor_command() {
+    let args = vec!["volume".to_string(), "list".to_string()];
+
+    let cmd = LocalExecutor::new().command("gluster", &args, true);
+    assert_eq!(cmd.get_program(), "sudo");
+    assert_eq!(
+        cmd.get_args().collect::<Vec<_>>(),
+        vec!["gluster", "volume", "list"]
+    );
+
+    let cmd = LocalExecutor::without_sudo().command("gluster", &args, true);
+    assert_eq!(cmd.get_program(), "gluster");
+    assert_eq!(cmd.get_args().collect::<Vec<_>>(), vec!["volume", "list"]);
+
+    let cmd = LocalExecutor::with_gluster_binary("/opt/gluster/sbin/gluster", true)
+        .command("gluster", &args, true);
+    assert_eq!(cmd.get_program(), "sudo");
+    assert_eq!(
+        cmd.get_args().collect::<Vec<_>>(),
+        vec!["/opt/gluster/sbin/gluster", "volume", "list"]
+    );
+
+    // Only the gluster binary is swapped and sudo is only used when asked for
+    let cmd = LocalExecutor::with_gluster_binary("/opt/gluster/sbin/gluster", true)
+        .command("ip", &args, false);
+    assert_eq!(cmd.get_program(), "ip");
+}
+
+/// Hands back canned output instead of running anything and remembers every
+/// command it was asked to run.
+#[cfg(test)]
+pub(crate) struct CannedExecutor {
+    responses: Vec<(Vec<String>, Output)>,
+    calls: ::std::sync::Mutex<Vec<Vec<String>>>,
+}
+
+#[cfg(test)]
+impl CannedExecutor {
+    pub(crate) fn new() -> CannedExecutor {
+        CannedExecutor {
+            responses: Vec::new(),
+            calls: ::std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answer any command whose arguments, ignoring --flags, start with
+    /// `args`.  The first matching response wins.  Anything unmatched
+    /// succeeds with no output.
+    pub(crate) fn respond(
+        mut self,
+        args: &[&str],
+        exit_code: i32,
+        stdout: &str,
+        stderr: &str,
+    ) -> CannedExecutor {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::ExitStatus;
+
+        self.responses.push((
+            args.iter().map(|a| a.to_string()).collect(),
+            Output {
+                status: ExitStatus::from_raw(exit_code << 8),
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: stderr.as_bytes().to_vec(),
+            },
+        ));
+        self
+    }
+
+    /// Every command run so far, each starting with the program name
+    pub(crate) fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl CommandExecutor for CannedExecutor {
+    fn execute(
+        &self,
+        command: &str,
+        args: &[String],
+        _as_root: bool,
+        _timeout: Option<Duration>,
+    ) -> Result<Output, GlusterError> {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::ExitStatus;
+
+        let mut argv = vec![command.to_string()];
+        argv.extend(args.iter().cloned());
+        self.calls.lock().unwrap().push(argv);
+
+        let plain_args: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+        for &(ref prefix, ref output) in &self.responses {
+            if plain_args.len() >= prefix.len()
+                && plain_args.iter().zip(prefix.iter()).all(|(a, p)| *a == p)
+            {
+                return Ok(output.clone());
+            }
+        }
+        Ok(Output {
+            status: ExitStatus::from_raw(0),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        })
+    }
+}
+
+#[test]
+fn test_canned_executor() {
+    let canned =
+        Arc::new(CannedExecutor::new().respond(&["volume", "list"], 0, "test\ntest2\n", ""));
+    // Not set_executor(): that would swap the executor under every other
+    // test running in parallel
+    let client = super::GlusterClient::builder()
+        .executor(canned.clone())
+        .build();
+    let volumes = client.volume_list();
+
+    assert_eq!(
+        volumes.unwrap(),
+        vec!["test".to_string(), "test2".to_string()]
+    );
+    assert_eq!(
+        canned.calls(),
+        vec![vec!["gluster", "--mode=script", "volume", "list"]]
+    );
+}
+
+#[test]
+fn test_local_executor_timeout() {
+    let executor = LocalExecutor::without_sudo();
+
+    let output = executor
+        .execute(
+            "echo",
+            &["hello".to_string()],
+            false,
+            Some(Duration::from_secs(10)),
+        )
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"hello\n".to_vec());
+
+    let start = Instant::now();
+    let result = executor.execute(
+        "sleep",
+        &["30".to_string()],
+        false,
+        Some(Duration::from_millis(100)),
+    );
+    assert!(start.elapsed() < Duration::from_secs(10));
+    match result {
+        Err(GlusterError::Timeout { argv, timeout }) => {
+            assert_eq!(argv, vec!["sleep", "30"]);
+            assert_eq!(timeout, Duration::from_millis(100));
+        }
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+}
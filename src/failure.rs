@@ -0,0 +1,143 @@
+//! Classification of the failure messages printed by the gluster CLI.
+//!
+//! glusterd reports most errors as free form text on stderr.  A handful of
+//! them come up again and again and call for a specific reaction from the
+//! caller, such as retrying later or fixing up a peer.  CliFailure picks those
+//! out so callers can match on them instead of on the text.
+
+/// A well known reason for a gluster CLI command to fail
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CliFailure {
+    /// Another glusterd transaction holds the cluster wide lock.  Retrying
+    /// after a short wait usually succeeds.
+    TransactionInProgress,
+    /// glusterd couldn't take the cluster wide lock on `host`
+    LockingFailed { host: String },
+    /// The brick is already part of a volume
+    BrickInUse { brick: String },
+    /// `host` rejected the operation while validating it.  reason is
+    /// whatever glusterd printed after the host name.
+    StagingFailed { host: String, reason: String },
+    /// `host` accepted the operation but failed to carry it out
+    CommitFailed { host: String, reason: String },
+    /// The peer `host` is not connected to this glusterd
+    PeerNotConnected { host: String },
+    /// Server or client quorum is not met so the operation was refused
+    QuorumNotMet,
+    /// The named volume doesn't exist
+    VolumeNotFound { volume: String },
+    /// `host` isn't a peer of this cluster
+    PeerNotFound { host: String },
+    /// The message didn't match anything known
+    Unknown,
+}
+
+impl CliFailure {
+    /// Classify a gluster CLI error message.  The first recognized failure
+    /// wins so a staging failure that wraps another error is reported as
+    /// StagingFailed.
+    pub fn classify(message: &str) -> CliFailure {
+        if let Some((host, reason)) = host_and_reason(message, "Staging failed on ") {
+            return CliFailure::StagingFailed { host, reason };
+        }
+        if let Some((host, reason)) = host_and_reason(message, "Commit failed on ") {
+            return CliFailure::CommitFailed { host, reason };
+        }
+        if message.contains("Another transaction is in progress")
+            || message.contains("Another transaction could be in progress")
+        {
+            return CliFailure::TransactionInProgress;
+        }
+        if let Some((host, _)) = host_and_reason(message, "Locking failed on ") {
+            return CliFailure::LockingFailed { host };
+        }
+        if let Some(brick) = word_before(message, " is already part of a volume") {
+            return CliFailure::BrickInUse { brick };
+        }
+        for prefix in &["Peer ", "Host "] {
+            if let Some(host) = word_between(message, prefix, " is not connected") {
+                return CliFailure::PeerNotConnected { host };
+            }
+        }
+        let lower = message.to_ascii_lowercase();
+        if lower.contains("quorum is not met") || lower.contains("quorum not met") {
+            return CliFailure::QuorumNotMet;
+        }
+        if let Some(volume) = word_between(message, "Volume ", " does not exist") {
+            return CliFailure::VolumeNotFound { volume };
+        }
+        if let Some(host) = word_before(message, " is not part of cluster") {
+            return CliFailure::PeerNotFound { host };
+        }
+        CliFailure::Unknown
+    }
+}
+
+// Host names end with the full stop that ends the sentence,
+// eg: "Staging failed on 10.0.0.2. Error: Volume test does not exist"
+fn trim_host(word: &str) -> String {
+    word.trim_end_matches(&['.', ',', ':'][..]).to_string()
+}
+
+// The word following `prefix` and whatever is left of its line
+fn host_and_reason(message: &str, prefix: &str) -> Option<(String, String)> {
+    let start = message.find(prefix)? + prefix.len();
+    let line = message[start..].lines().next().unwrap_or("");
+    let mut parts = line.splitn(2, char::is_whitespace);
+    let host = trim_host(parts.next()?);
+    if host.is_empty() {
+        return None;
+    }
+    let reason = parts.next().unwrap_or("").trim();
+    let reason = reason.trim_start_matches("Error:").trim();
+    Some((host, reason.to_string()))
+}
+
+// The last word before `suffix`
+fn word_before(message: &str, suffix: &str) -> Option<String> {
+    let end = message.find(suffix)?;
+    let word = message[..end].split_whitespace().last()?;
+    Some(word.to_string())
+}
+
+// The single word between `prefix` and `suffix`,
+// eg: "Peer node2 is not connected"
+fn word_between(message: &str, prefix: &str, suffix: &str) -> Option<String> {
+    for (end, _) in message.match_indices(suffix) {
+        if let Some(start) = message[..end].rfind(prefix) {
+            let word = &message[start + prefix.len()..end];
+            if !word.is_empty() && !word.contains(char::is_whitespace) {
+                return Some(word.to_string());
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn test_classify_extracts_host() {
+    assert_eq!(
+        CliFailure::classify(
+            "volume set: failed: Staging failed on gluster-2.example.com. \
+             Please check log file for details.\n"
+        ),
+        CliFailure::StagingFailed {
+            host: "gluster-2.example.com".to_string(),
+            reason: "Please check log file for details.".to_string(),
+        }
+    );
+    assert_eq!(
+        CliFailure::classify("volume start: test: failed: Commit failed on localhost"),
+        CliFailure::CommitFailed {
+            host: "localhost".to_string(),
+            reason: "".to_string(),
+        }
+    );
+    assert_eq!(
+        CliFailure::classify("peer detach: failed: node5 is not part of cluster"),
+        CliFailure::PeerNotFound {
+            host: "node5".to_string(),
+        }
+    );
+    assert_eq!(CliFailure::classify(""), CliFailure::Unknown);
+}
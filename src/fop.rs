@@ -135,7 +135,7 @@ impl FromStr for GlusterFOP {
             "LOW" => Ok(GlusterFOP::GfFopFallocate),
             "NORMAL" => Ok(GlusterFOP::GfFopIpc),
             "LEAST" => Ok(GlusterFOP::GfFopDiscard),
-            _ => Err(GlusterError::new(format!("Unknown FOP: {}", s))),
+            _ => Err(GlusterError::parse_failure(s, "FOP")),
         }
     }
 }
@@ -233,10 +233,7 @@ fn test_parse_fop_sample() {
 fn parse_fop_sample(input: &str) -> Result<GlusterFOPSample, GlusterError> {
     let parts: Vec<&str> = input.split(',').collect();
     if parts.len() != 10 {
-        return Err(GlusterError::new(format!(
-            "Invalid FOP sample: {}.  Unable to parse",
-            input
-        )));
+        return Err(GlusterError::parse_failure(input, "FOP sample"));
     }
     Ok(GlusterFOPSample {
         time: parts[0].to_string(),
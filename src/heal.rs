@@ -1,7 +1,7 @@
 use std::fs::read_dir;
 use std::path::Path;
 
-use super::GlusterError;
+use super::{GlusterClient, GlusterError};
 use volume::Brick;
 
 /// Find the self heal count for a given brick
@@ -19,3 +19,28 @@ pub fn get_self_heal_count(brick: &Brick) -> Result<usize, GlusterError> {
         .count();
     Ok(entry_count)
 }
+
+/// Start healing the files that need it on a replicated or dispersed volume.
+/// Setting full crawls the entire volume instead of only the files marked as
+/// needing a heal.
+/// # Failures
+/// Will return GlusterError if the command fails to run
+pub fn volume_heal(volume: &str, full: bool) -> Result<i32, GlusterError> {
+    GlusterClient::default().volume_heal(volume, full)
+}
+
+impl GlusterClient {
+    /// Start healing the files that need it on a replicated or dispersed volume.
+    /// Setting full crawls the entire volume instead of only the files marked as
+    /// needing a heal.
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_heal(&self, volume: &str, full: bool) -> Result<i32, GlusterError> {
+        let mut arg_list: Vec<&str> = vec!["volume", "heal", volume];
+        if full {
+            arg_list.push("full");
+        }
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+}
@@ -0,0 +1,233 @@
+//! Host names as glusterd knows them.
+//!
+//! Peers and bricks are named by whatever the administrator typed when
+//! probing or creating the volume: a short name, an FQDN or an address.  A
+//! Host keeps that name as is and only looks up its addresses when they are
+//! needed to tell whether two differently named hosts are the same machine.
+//! Lookups go through a Resolver so they can be swapped out.
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::OnceLock;
+
+use super::GlusterError;
+
+/// Looks up the addresses of a host name
+pub trait Resolver: Send + Sync {
+    /// Every address `name` resolves to
+    /// # Failures
+    /// Returns GlusterError if the name can't be resolved
+    fn resolve(&self, name: &str) -> Result<Vec<IpAddr>, GlusterError>;
+}
+
+/// Resolves names through std::net::ToSocketAddrs, which uses the system's
+/// resolver and honors /etc/hosts
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve(&self, name: &str) -> Result<Vec<IpAddr>, GlusterError> {
+        let mut addresses: Vec<IpAddr> = Vec::new();
+        for addr in (name, 0).to_socket_addrs()? {
+            if !addresses.contains(&addr.ip()) {
+                addresses.push(addr.ip());
+            }
+        }
+        Ok(addresses)
+    }
+}
+
+/// A host name or address with its addresses looked up at most once
+#[derive(Clone)]
+pub struct Host {
+    name: String,
+    addresses: OnceLock<Vec<IpAddr>>,
+}
+
+impl Host {
+    pub fn new<S: Into<String>>(name: S) -> Host {
+        Host {
+            name: name.into(),
+            addresses: OnceLock::new(),
+        }
+    }
+
+    /// The name exactly as it was given
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name as an address if it is one
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.name.parse::<IpAddr>().ok()
+    }
+
+    /// The addresses this host resolves to.  The first successful lookup is
+    /// remembered.  Addresses resolve to themselves without a lookup.
+    /// # Failures
+    /// Returns GlusterError if the name can't be resolved
+    pub fn addresses(&self, resolver: &dyn Resolver) -> Result<&[IpAddr], GlusterError> {
+        if let Some(addresses) = self.addresses.get() {
+            return Ok(addresses);
+        }
+        let addresses = match self.ip() {
+            Some(ip) => vec![ip],
+            None => resolver.resolve(&self.name)?,
+        };
+        debug!("{} resolved to {:?}", self.name, addresses);
+        Ok(self.addresses.get_or_init(|| addresses))
+    }
+
+    /// Whether both hosts are the same machine: they have the same name or
+    /// share an address
+    /// # Failures
+    /// Returns GlusterError if either name can't be resolved
+    pub fn matches(&self, other: &Host, resolver: &dyn Resolver) -> Result<bool, GlusterError> {
+        if self == other {
+            return Ok(true);
+        }
+        let addresses = self.addresses(resolver)?;
+        Ok(other
+            .addresses(resolver)?
+            .iter()
+            .any(|a| addresses.contains(a)))
+    }
+}
+
+impl PartialEq for Host {
+    fn eq(&self, other: &Host) -> bool {
+        self.name == other.name
+    }
+}
+impl Eq for Host {}
+
+impl PartialEq<str> for Host {
+    fn eq(&self, other: &str) -> bool {
+        self.name == other
+    }
+}
+impl<'a> PartialEq<&'a str> for Host {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.name == *other
+    }
+}
+impl PartialEq<String> for Host {
+    fn eq(&self, other: &String) -> bool {
+        self.name == *other
+    }
+}
+
+impl Hash for Host {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+impl Ord for Host {
+    fn cmp(&self, other: &Host) -> Ordering {
+        self.name.cmp(&other.name)
+    }
+}
+impl PartialOrd for Host {
+    fn partial_cmp(&self, other: &Host) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Host {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl fmt::Debug for Host {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.name)
+    }
+}
+
+impl<'a> From<&'a str> for Host {
+    fn from(name: &'a str) -> Host {
+        Host::new(name)
+    }
+}
+
+impl From<String> for Host {
+    fn from(name: String) -> Host {
+        Host::new(name)
+    }
+}
+
+/// Resolves names from a fixed table and remembers every name it was asked
+/// for.  Names missing from the table fail to resolve.
+#[cfg(test)]
+pub(crate) struct CannedResolver {
+    hosts: Vec<(String, Vec<IpAddr>)>,
+    lookups: ::std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl CannedResolver {
+    pub(crate) fn new() -> CannedResolver {
+        CannedResolver {
+            hosts: Vec::new(),
+            lookups: ::std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn host(mut self, name: &str, addresses: &[&str]) -> CannedResolver {
+        self.hosts.push((
+            name.to_string(),
+            addresses.iter().map(|a| a.parse().unwrap()).collect(),
+        ));
+        self
+    }
+
+    /// Every name looked up so far
+    pub(crate) fn lookups(&self) -> Vec<String> {
+        self.lookups.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl Resolver for CannedResolver {
+    fn resolve(&self, name: &str) -> Result<Vec<IpAddr>, GlusterError> {
+        self.lookups.lock().unwrap().push(name.to_string());
+        match self.hosts.iter().find(|h| h.0 == name) {
+            Some(host) => Ok(host.1.clone()),
+            None => Err(GlusterError::new(format!("Unable to resolve {}", name))),
+        }
+    }
+}
+
+#[test]
+fn test_host_matches() {
+    let resolver = CannedResolver::new()
+        .host("gluster-1", &["10.0.0.1", "fd00::1"])
+        .host("gluster-1.example.com", &["10.0.0.1"])
+        .host("gluster-2", &["10.0.0.2"]);
+
+    let host = Host::new("gluster-1");
+    assert_eq!(host, "gluster-1");
+    assert_eq!(host.to_string(), "gluster-1");
+    assert!(host.ip().is_none());
+    assert!(host
+        .matches(&Host::new("gluster-1.example.com"), &resolver)
+        .unwrap());
+    assert!(host.matches(&Host::new("fd00::1"), &resolver).unwrap());
+    assert!(!host.matches(&Host::new("gluster-2"), &resolver).unwrap());
+    // Each name is looked up once no matter how often it is compared and
+    // addresses aren't looked up at all
+    assert!(host.matches(&Host::new("10.0.0.1"), &resolver).unwrap());
+    assert_eq!(
+        resolver.lookups(),
+        vec!["gluster-1", "gluster-1.example.com", "gluster-2"]
+    );
+
+    assert!(host.matches(&Host::new("gluster-3"), &resolver).is_err());
+    // The same name matches without any lookup
+    assert!(Host::new("gluster-3")
+        .matches(&Host::new("gluster-3"), &resolver)
+        .unwrap());
+}
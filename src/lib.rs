@@ -14,26 +14,37 @@
 //! Please file any bugs found at: [Gluster
 //! Repo](https://github.com/cholcombe973/Gluster)
 //! Pull requests are more than welcome!
+pub mod client;
+pub mod executor;
+pub mod failure;
 pub mod fop;
 pub mod heal;
+pub mod host;
+mod local;
+pub mod options;
 pub mod peer;
+pub mod retry;
 mod rpc;
+pub mod store;
+pub mod version;
 pub mod volume;
 
 extern crate byteorder;
+extern crate libc;
 #[macro_use]
 extern crate log;
 extern crate regex;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
+extern crate serde_xml_rs;
 extern crate unix_socket;
 extern crate uuid;
 
 use std::cmp::Ord;
 use std::cmp::Ordering;
 use std::error::Error;
-use std::ffi::OsStr;
+use std::fmt;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
@@ -41,7 +52,11 @@ use std::net::IpAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use volume::{volume_info, Brick};
+pub use client::{GlusterClient, GlusterClientBuilder};
+pub use failure::CliFailure;
+pub use host::Host;
+pub use version::{Feature, GlusterVersion};
+use volume::Brick;
 
 // "%0.6lf,%s,%s,%0.4lf,%s,%s,%s,%s,%s,%s",
 // epoch_time, fop_enum_to_pri_string (sample->fop_type),
@@ -50,30 +65,37 @@ use volume::{volume_info, Brick};
 // group_name, hostname, port);
 //
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum SelfHealAlgorithm {
     Full,
     Diff,
     Reset,
 }
 
-impl SelfHealAlgorithm {
-    fn to_string(&self) -> String {
-        match *self {
-            SelfHealAlgorithm::Full => "full".to_string(),
-            SelfHealAlgorithm::Diff => "diff".to_string(),
-            SelfHealAlgorithm::Reset => "reset".to_string(),
-        }
+impl fmt::Display for SelfHealAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            SelfHealAlgorithm::Full => "full",
+            SelfHealAlgorithm::Diff => "diff",
+            SelfHealAlgorithm::Reset => "reset",
+        })
     }
-    fn from_str(s: &str) -> SelfHealAlgorithm {
-        match s {
-            "full" => SelfHealAlgorithm::Full,
-            "diff" => SelfHealAlgorithm::Diff,
-            "reset" => SelfHealAlgorithm::Reset,
-            _ => SelfHealAlgorithm::Full,
+}
+
+impl FromStr for SelfHealAlgorithm {
+    type Err = GlusterError;
+
+    fn from_str(s: &str) -> Result<SelfHealAlgorithm, GlusterError> {
+        match s.trim() {
+            "full" => Ok(SelfHealAlgorithm::Full),
+            "diff" => Ok(SelfHealAlgorithm::Diff),
+            "reset" => Ok(SelfHealAlgorithm::Reset),
+            _ => Err(GlusterError::parse_failure(s, "self heal algorithm")),
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum SplitBrainPolicy {
     Ctime,
     Disable,
@@ -82,24 +104,23 @@ pub enum SplitBrainPolicy {
     Size,
 }
 
-impl SplitBrainPolicy {
-    pub fn to_string(&self) -> String {
-        match *self {
-            SplitBrainPolicy::Ctime => "ctime".to_string(),
-            SplitBrainPolicy::Disable => "none".to_string(),
-            SplitBrainPolicy::Majority => "majority".to_string(),
-            SplitBrainPolicy::Mtime => "mtime".to_string(),
-            SplitBrainPolicy::Size => "size".to_string(),
-        }
+impl fmt::Display for SplitBrainPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str((*self).into())
     }
-    pub fn from_str(s: &str) -> Result<SplitBrainPolicy, GlusterError> {
-        match s {
+}
+
+impl FromStr for SplitBrainPolicy {
+    type Err = GlusterError;
+
+    fn from_str(s: &str) -> Result<SplitBrainPolicy, GlusterError> {
+        match s.trim() {
             "ctime" => Ok(SplitBrainPolicy::Ctime),
             "none" => Ok(SplitBrainPolicy::Disable),
             "majority" => Ok(SplitBrainPolicy::Majority),
             "mtime" => Ok(SplitBrainPolicy::Mtime),
             "size" => Ok(SplitBrainPolicy::Size),
-            _ => Err(GlusterError::new("Unknown access mode".to_string())),
+            _ => Err(GlusterError::parse_failure(s, "split brain policy")),
         }
     }
 }
@@ -115,23 +136,26 @@ impl<'a> Into<&'a str> for SplitBrainPolicy {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum AccessMode {
     ReadOnly,
     ReadWrite,
 }
 
-impl AccessMode {
-    pub fn to_string(&self) -> String {
-        match *self {
-            AccessMode::ReadOnly => "read-only".to_string(),
-            AccessMode::ReadWrite => "read-write".to_string(),
-        }
+impl fmt::Display for AccessMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str((*self).into())
     }
-    pub fn from_str(s: &str) -> AccessMode {
-        match s {
-            "read-only" => AccessMode::ReadOnly,
-            "read-write" => AccessMode::ReadWrite,
-            _ => AccessMode::ReadWrite,
+}
+
+impl FromStr for AccessMode {
+    type Err = GlusterError;
+
+    fn from_str(s: &str) -> Result<AccessMode, GlusterError> {
+        match s.trim() {
+            "read-only" => Ok(AccessMode::ReadOnly),
+            "read-write" => Ok(AccessMode::ReadWrite),
+            _ => Err(GlusterError::parse_failure(s, "access mode")),
         }
     }
 }
@@ -145,6 +169,7 @@ impl<'a> Into<&'a str> for AccessMode {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Toggle {
     On,
     Off,
@@ -159,24 +184,29 @@ impl Into<bool> for Toggle {
     }
 }
 
-impl Toggle {
-    pub fn to_string(&self) -> String {
-        match *self {
-            Toggle::On => "On".to_string(),
-            Toggle::Off => "Off".to_string(),
-        }
+impl fmt::Display for Toggle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Toggle::On => "on",
+            Toggle::Off => "off",
+        })
     }
-    pub fn from_str(s: &str) -> Toggle {
-        match s {
-            "on" => Toggle::On,
-            "off" => Toggle::Off,
-            "true" => Toggle::On,
-            "false" => Toggle::Off,
-            _ => Toggle::Off,
+}
+
+impl FromStr for Toggle {
+    type Err = GlusterError;
+
+    /// Accepts every spelling glusterd does for a boolean
+    fn from_str(s: &str) -> Result<Toggle, GlusterError> {
+        match s.trim().to_ascii_lowercase().as_ref() {
+            "on" | "true" | "yes" | "enable" | "1" => Ok(Toggle::On),
+            "off" | "false" | "no" | "disable" | "0" => Ok(Toggle::Off),
+            _ => Err(GlusterError::parse_failure(s, "toggle")),
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum ScrubSchedule {
     Hourly,
     Daily,
@@ -184,46 +214,60 @@ pub enum ScrubSchedule {
     BiWeekly,
     Monthly,
 }
-impl ScrubSchedule {
-    pub fn to_string(&self) -> String {
-        match *self {
-            ScrubSchedule::Hourly => "hourly".to_string(),
-            ScrubSchedule::Daily => "daily".to_string(),
-            ScrubSchedule::Weekly => "weekly".to_string(),
-            ScrubSchedule::BiWeekly => "biweekly".to_string(),
-            ScrubSchedule::Monthly => "monthly".to_string(),
-        }
+
+impl fmt::Display for ScrubSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ScrubSchedule::Hourly => "hourly",
+            ScrubSchedule::Daily => "daily",
+            ScrubSchedule::Weekly => "weekly",
+            ScrubSchedule::BiWeekly => "biweekly",
+            ScrubSchedule::Monthly => "monthly",
+        })
     }
-    pub fn from_str(s: &str) -> ScrubSchedule {
-        match s {
-            "hourly" => ScrubSchedule::Hourly,
-            "daily" => ScrubSchedule::Daily,
-            "weekly" => ScrubSchedule::Weekly,
-            "biweekly" => ScrubSchedule::BiWeekly,
-            "monthly" => ScrubSchedule::Monthly,
-            _ => ScrubSchedule::Weekly,
+}
+
+impl FromStr for ScrubSchedule {
+    type Err = GlusterError;
+
+    fn from_str(s: &str) -> Result<ScrubSchedule, GlusterError> {
+        match s.trim() {
+            "hourly" => Ok(ScrubSchedule::Hourly),
+            "daily" => Ok(ScrubSchedule::Daily),
+            "weekly" => Ok(ScrubSchedule::Weekly),
+            "biweekly" => Ok(ScrubSchedule::BiWeekly),
+            "monthly" => Ok(ScrubSchedule::Monthly),
+            _ => Err(GlusterError::parse_failure(s, "scrub schedule")),
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum ScrubAggression {
     Aggressive,
     Lazy,
     Normal,
 }
-impl ScrubAggression {
-    pub fn to_string(&self) -> String {
-        match *self {
-            ScrubAggression::Aggressive => "aggressive".to_string(),
-            ScrubAggression::Lazy => "lazy".to_string(),
-            ScrubAggression::Normal => "normal".to_string(),
-        }
+
+impl fmt::Display for ScrubAggression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ScrubAggression::Aggressive => "aggressive",
+            ScrubAggression::Lazy => "lazy",
+            ScrubAggression::Normal => "normal",
+        })
     }
-    pub fn from_str(s: &str) -> ScrubAggression {
-        match s {
-            "aggressive" => ScrubAggression::Aggressive,
-            "lazy" => ScrubAggression::Lazy,
-            "normal" => ScrubAggression::Normal,
-            _ => ScrubAggression::Normal,
+}
+
+impl FromStr for ScrubAggression {
+    type Err = GlusterError;
+
+    fn from_str(s: &str) -> Result<ScrubAggression, GlusterError> {
+        match s.trim() {
+            "aggressive" => Ok(ScrubAggression::Aggressive),
+            "lazy" => Ok(ScrubAggression::Lazy),
+            "normal" => Ok(ScrubAggression::Normal),
+            _ => Err(GlusterError::parse_failure(s, "scrub throttle")),
         }
     }
 }
@@ -282,6 +326,7 @@ impl BitrotOption {
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum GlusterOption {
     /// Valid IP address which includes wild card patterns including *, such as 192.168.1.*
     AuthAllow(String),
@@ -438,10 +483,13 @@ pub enum GlusterOption {
     /// Number of seconds between health-checks done on the filesystem that is used for the
     /// brick(s). Defaults to 30 seconds, set to 0 to disable.
     StorageHealthCheckInterval(u16),
+    /// Any other option, as its key and value
+    Unknown(String, String),
 }
 
 impl GlusterOption {
-    fn to_string(&self) -> String {
+    /// The volume option key as glusterd spells it, eg: "nfs.disable"
+    pub fn key(&self) -> String {
         match *self {
             GlusterOption::AuthAllow(_) => "auth.allow".to_string(),
             GlusterOption::AuthReject(_) => "auth.reject".to_string(),
@@ -526,9 +574,13 @@ impl GlusterOption {
             GlusterOption::StorageHealthCheckInterval(_) => {
                 "storage.health-check-interval".to_string()
             }
+            GlusterOption::Unknown(ref key, _) => key.clone(),
         }
     }
-    fn value(&self) -> String {
+
+    /// The value as it is passed to `gluster volume set`.  Parsing it with
+    /// from_str gives back an equal option.
+    pub fn value(&self) -> String {
         match *self {
             GlusterOption::AuthAllow(ref val) => val.to_string(),
             GlusterOption::AuthReject(ref val) => val.to_string(),
@@ -536,12 +588,12 @@ impl GlusterOption {
             GlusterOption::ClientSsl(ref val) => val.to_string(),
             GlusterOption::ClusterSelfHealWindowSize(val) => val.to_string(),
             GlusterOption::ClusterDataSelfHealAlgorithm(ref val) => val.to_string(),
-            GlusterOption::ClusterMinFreeDisk(val) => val.to_string(),
+            GlusterOption::ClusterMinFreeDisk(val) => format!("{}%", val),
             GlusterOption::ClusterStripeBlockSize(val) => val.to_string(),
             GlusterOption::ClusterSelfHealDaemon(ref val) => val.to_string(),
             GlusterOption::ClusterEnsureDurability(ref val) => val.to_string(),
-            GlusterOption::DiagnosticsBrickLevel(val) => val.to_string(),
-            GlusterOption::DiagnosticsClientLevel(val) => val.to_string(),
+            GlusterOption::DiagnosticsBrickLevel(val) => log_level_value(val),
+            GlusterOption::DiagnosticsClientLevel(val) => log_level_value(val),
             GlusterOption::DiagnosticsLatencyMeasurement(ref val) => val.to_string(),
             GlusterOption::DiagnosticsDumpFdStats(ref val) => val.to_string(),
             GlusterOption::DiagnosticsFopSampleInterval(ref val) => val.to_string(),
@@ -585,63 +637,87 @@ impl GlusterOption {
             GlusterOption::SslCipherList(ref val) => val.to_string(),
             GlusterOption::ServerStatedumpPath(ref val) => val.to_string_lossy().into_owned(),
             GlusterOption::StorageHealthCheckInterval(val) => val.to_string(),
+            GlusterOption::Unknown(_, ref val) => val.clone(),
         }
     }
+
+    /// Parse an option from its key and value as `gluster volume get` and
+    /// volume info print them.  Keys this library doesn't know become
+    /// GlusterOption::Unknown, as do values glusterd accepts but the
+    /// variant can't hold.  The older dashed keys, eg: "nfs-disable", are
+    /// accepted too.
+    /// # Failures
+    /// Returns GlusterError if the value isn't valid for a known key
     pub fn from_str(s: &str, value: String) -> Result<GlusterOption, GlusterError> {
-        match s {
-            "auth-allow" => Ok(GlusterOption::AuthAllow(value)),
-            "auth-reject" => Ok(GlusterOption::AuthReject(value)),
+        let key = canonical_option_key(s.trim());
+        let value = value.trim().to_string();
+        match GlusterOption::typed(key, value.clone()) {
+            // glusterd accepts values some variants can't hold, eg: the
+            // CRITICAL log level or a cluster.min-free-disk of 10GB.  Those
+            // are kept as they are.
+            Err(e) => match options::constraint(key) {
+                Some(c) if c.check(&value).is_none() => {
+                    Ok(GlusterOption::Unknown(key.to_string(), value))
+                }
+                _ => Err(e),
+            },
+            option => option,
+        }
+    }
+
+    fn typed(key: &str, value: String) -> Result<GlusterOption, GlusterError> {
+        match key {
+            "auth.allow" => Ok(GlusterOption::AuthAllow(value)),
+            "auth.reject" => Ok(GlusterOption::AuthReject(value)),
             "auth.ssl-allow" => Ok(GlusterOption::SslAllow(value)),
-            "client.ssl" => {
-                let t = Toggle::from_str(&value);
-                Ok(GlusterOption::ClientSsl(t))
-            }
+            "client.ssl" => Ok(GlusterOption::ClientSsl(Toggle::from_str(&value)?)),
             "cluster.favorite-child-policy" => {
                 let policy = SplitBrainPolicy::from_str(&value)?;
                 Ok(GlusterOption::FavoriteChildPolicy(policy))
             }
-            "client-grace-timeout" => {
+            "client.grace-timeout" => {
                 let i = i64::from_str(&value)?;
                 Ok(GlusterOption::ClientGraceTimeout(i))
             }
-            "cluster-self-heal-window-size" => {
+            "cluster.self-heal-window-size" => {
                 let i = u16::from_str(&value)?;
                 Ok(GlusterOption::ClusterSelfHealWindowSize(i))
             }
-            "cluster-data-self-heal-algorithm" => {
-                let s = SelfHealAlgorithm::from_str(&value);
+            "cluster.data-self-heal-algorithm" => {
+                let s = SelfHealAlgorithm::from_str(&value)?;
                 Ok(GlusterOption::ClusterDataSelfHealAlgorithm(s))
             }
-            "cluster-min-free-disk" => {
-                let i = u8::from_str(&value)?;
+            "cluster.min-free-disk" => {
+                // glusterd prints it as a percentage
+                let i = u8::from_str(value.trim_end_matches('%'))?;
                 Ok(GlusterOption::ClusterMinFreeDisk(i))
             }
-            "cluster-stripe-block-size" => {
-                let i = u64::from_str(&value)?;
+            "cluster.stripe-block-size" => {
+                let i = parse_size(&value)?;
                 Ok(GlusterOption::ClusterStripeBlockSize(i))
             }
-            "cluster-self-heal-daemon" => {
-                let t = Toggle::from_str(&value);
+            "cluster.self-heal-daemon" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::ClusterSelfHealDaemon(t))
             }
-            "cluster-ensure-durability" => {
-                let t = Toggle::from_str(&value);
+            "cluster.ensure-durability" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::ClusterEnsureDurability(t))
             }
-            "diagnostics-brick-log-level" => {
-                let l = log::Level::from_str(&value).unwrap_or(log::Level::Debug);
+            "diagnostics.brick-log-level" => {
+                let l = parse_log_level(&value)?;
                 Ok(GlusterOption::DiagnosticsBrickLevel(l))
             }
-            "diagnostics-client-log-level" => {
-                let l = log::Level::from_str(&value).unwrap_or(log::Level::Debug);
+            "diagnostics.client-log-level" => {
+                let l = parse_log_level(&value)?;
                 Ok(GlusterOption::DiagnosticsClientLevel(l))
             }
-            "diagnostics-latency-measurement" => {
-                let t = Toggle::from_str(&value);
+            "diagnostics.latency-measurement" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::DiagnosticsLatencyMeasurement(t))
             }
             "diagnostics.count-fop-hits" => {
-                let t = Toggle::from_str(&value);
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::DiagnosticsCountFopHits(t))
             }
             "diagnostics.stats-dump-interval" => {
@@ -660,128 +736,125 @@ impl GlusterOption {
                 let i = u64::from_str(&value)?;
                 Ok(GlusterOption::DiagnosticsStatsDnscacheTtlSec(i))
             }
-            "diagnostics-dump-fd-stats" => {
-                let t = Toggle::from_str(&value);
+            "diagnostics.dump-fd-stats" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::DiagnosticsDumpFdStats(t))
             }
-            "features-read-only" => {
-                let t = Toggle::from_str(&value);
+            "features.read-only" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::FeaturesReadOnly(t))
             }
-            "features-lock-heal" => {
-                let t = Toggle::from_str(&value);
+            "features.lock-heal" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::FeaturesLockHeal(t))
             }
-            "features-quota-timeout" => {
+            "features.quota-timeout" => {
                 let i = u16::from_str(&value)?;
                 Ok(GlusterOption::FeaturesQuotaTimeout(i))
             }
-            "geo-replication-indexing" => {
-                let t = Toggle::from_str(&value);
+            "geo-replication.indexing" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::GeoReplicationIndexing(t))
             }
-            "network-frame-timeout" => {
+            "network.frame-timeout" => {
                 let i = u16::from_str(&value)?;
                 Ok(GlusterOption::NetworkFrameTimeout(i))
             }
-            "nfs-enable-ino32" => {
-                let t = Toggle::from_str(&value);
+            "nfs.enable-ino32" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsEnableIno32(t))
             }
-            "nfs-volume-access" => {
-                let s = AccessMode::from_str(&value);
+            "nfs.volume-access" => {
+                let s = AccessMode::from_str(&value)?;
                 Ok(GlusterOption::NfsVolumeAccess(s))
             }
-            "nfs-trusted-write" => {
-                let t = Toggle::from_str(&value);
+            "nfs.trusted-write" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsTrustedWrite(t))
             }
-            "nfs-trusted-sync" => {
-                let t = Toggle::from_str(&value);
+            "nfs.trusted-sync" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsTrustedSync(t))
             }
-            "nfs-export-dir" => Ok(GlusterOption::NfsExportDir(value)),
-            "nfs-export-volumes" => {
-                let t = Toggle::from_str(&value);
+            "nfs.export-dir" => Ok(GlusterOption::NfsExportDir(value)),
+            "nfs.export-volumes" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsExportVolumes(t))
             }
-            "nfs-rpc-auth-unix" => {
-                let t = Toggle::from_str(&value);
+            "nfs.rpc-auth-unix" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsRpcAuthUnix(t))
             }
-            "nfs-rpc-auth-null" => {
-                let t = Toggle::from_str(&value);
+            "nfs.rpc-auth-null" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsRpcAuthNull(t))
             }
-            "nfs-ports-insecure" => {
-                let t = Toggle::from_str(&value);
+            "nfs.ports-insecure" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsPortsInsecure(t))
             }
-            "nfs-addr-namelookup" => {
-                let t = Toggle::from_str(&value);
+            "nfs.addr-namelookup" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsAddrNamelookup(t))
             }
-            "nfs-register-with-portmap" => {
-                let t = Toggle::from_str(&value);
+            "nfs.register-with-portmap" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsRegisterWithPortmap(t))
             }
-            "nfs-disable" => {
-                let t = Toggle::from_str(&value);
+            "nfs.disable" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::NfsDisable(t))
             }
-            "performance-write-behind-window-size" => {
-                let i = u64::from_str(&value)?;
+            "performance.write-behind-window-size" => {
+                let i = parse_size(&value)?;
                 Ok(GlusterOption::PerformanceWriteBehindWindowSize(i))
             }
-            "performance-io-thread-count" => {
+            "performance.io-thread-count" => {
                 let i = u8::from_str(&value)?;
                 Ok(GlusterOption::PerformanceIoThreadCount(i))
             }
-            "performance-flush-behind" => {
-                let t = Toggle::from_str(&value);
+            "performance.flush-behind" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::PerformanceFlushBehind(t))
             }
-            "performance-cache-max-file-size" => {
-                let i = u64::from_str(&value)?;
+            "performance.cache-max-file-size" => {
+                let i = parse_size(&value)?;
                 Ok(GlusterOption::PerformanceCacheMaxFileSize(i))
             }
-            "performance-cache-min-file-size" => {
-                let i = u64::from_str(&value)?;
+            "performance.cache-min-file-size" => {
+                let i = parse_size(&value)?;
                 Ok(GlusterOption::PerformanceCacheMinFileSize(i))
             }
-            "performance-cache-refresh-timeout" => {
+            "performance.cache-refresh-timeout" => {
                 let i = u8::from_str(&value)?;
                 Ok(GlusterOption::PerformanceCacheRefreshTimeout(i))
             }
-            "performance-cache-size" => {
-                let i = u64::from_str(&value)?;
+            "performance.cache-size" => {
+                let i = parse_size(&value)?;
                 Ok(GlusterOption::PerformanceCacheSize(i))
             }
-            "performance-readdir-ahead" => {
-                let t = Toggle::from_str(&value);
+            "performance.readdir-ahead" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::PerformanceReadDirAhead(t))
             }
-            "performance-parallel-readdir" => {
-                let t = Toggle::from_str(&value);
-                Ok(GlusterOption::PerformanceReadDirAhead(t))
+            "performance.parallel-readdir" => {
+                let t = Toggle::from_str(&value)?;
+                Ok(GlusterOption::PerformanceParallelReadDir(t))
             }
-            "performance-readdir-cache-limit" => {
-                let i = u64::from_str(&value)?;
+            "performance.rda-cache-limit" => {
+                let i = parse_size(&value)?;
                 Ok(GlusterOption::PerformanceReadDirAheadCacheLimit(i))
             }
-            "server.ssl" => {
-                let t = Toggle::from_str(&value);
-                Ok(GlusterOption::ServerSsl(t))
-            }
-            "server-allow-insecure" => {
-                let t = Toggle::from_str(&value);
+            "server.ssl" => Ok(GlusterOption::ServerSsl(Toggle::from_str(&value)?)),
+            "server.allow-insecure" => {
+                let t = Toggle::from_str(&value)?;
                 Ok(GlusterOption::ServerAllowInsecure(t))
             }
-            "server-grace-timeout" => {
+            "server.grace-timeout" => {
                 let i = u16::from_str(&value)?;
                 Ok(GlusterOption::ServerGraceTimeout(i))
             }
-            "server-statedump-path" => {
+            "server.statedump-path" => {
                 let p = PathBuf::from(&value);
                 Ok(GlusterOption::ServerStatedumpPath(p))
             }
@@ -790,15 +863,235 @@ impl GlusterOption {
                 Ok(GlusterOption::SslCertificateDepth(i))
             }
             "ssl.cipher-list" => Ok(GlusterOption::SslCipherList(value)),
-            "storage-health-check-interval" => {
+            "storage.health-check-interval" => {
                 let i = u16::from_str(&value)?;
                 Ok(GlusterOption::StorageHealthCheckInterval(i))
             }
-            _ => Err(GlusterError::new(format!("Unknown option: {}", s))),
+            key => Ok(GlusterOption::Unknown(key.to_string(), value)),
+        }
+    }
+}
+
+/// The key as glusterd spells it for the older dashed keys, eg:
+/// "nfs-disable" is "nfs.disable".  Other keys are returned as they are.
+pub(crate) fn canonical_option_key(key: &str) -> &str {
+    match key {
+        "auth-allow" => "auth.allow",
+        "auth-reject" => "auth.reject",
+        "client-grace-timeout" => "client.grace-timeout",
+        "cluster-self-heal-window-size" => "cluster.self-heal-window-size",
+        "cluster-data-self-heal-algorithm" => "cluster.data-self-heal-algorithm",
+        "cluster-min-free-disk" => "cluster.min-free-disk",
+        "cluster-stripe-block-size" => "cluster.stripe-block-size",
+        "cluster-self-heal-daemon" => "cluster.self-heal-daemon",
+        "cluster-ensure-durability" => "cluster.ensure-durability",
+        "diagnostics-brick-log-level" => "diagnostics.brick-log-level",
+        "diagnostics-client-log-level" => "diagnostics.client-log-level",
+        "diagnostics-latency-measurement" => "diagnostics.latency-measurement",
+        "diagnostics-dump-fd-stats" => "diagnostics.dump-fd-stats",
+        "features-read-only" => "features.read-only",
+        "features-lock-heal" => "features.lock-heal",
+        "features-quota-timeout" => "features.quota-timeout",
+        "geo-replication-indexing" => "geo-replication.indexing",
+        "network-frame-timeout" => "network.frame-timeout",
+        "nfs-enable-ino32" => "nfs.enable-ino32",
+        "nfs-volume-access" => "nfs.volume-access",
+        "nfs-trusted-write" => "nfs.trusted-write",
+        "nfs-trusted-sync" => "nfs.trusted-sync",
+        "nfs-export-dir" => "nfs.export-dir",
+        "nfs-export-volumes" => "nfs.export-volumes",
+        "nfs-rpc-auth-unix" => "nfs.rpc-auth-unix",
+        "nfs-rpc-auth-null" => "nfs.rpc-auth-null",
+        "nfs-ports-insecure" => "nfs.ports-insecure",
+        "nfs-addr-namelookup" => "nfs.addr-namelookup",
+        "nfs-register-with-portmap" => "nfs.register-with-portmap",
+        "nfs-disable" => "nfs.disable",
+        "performance-write-behind-window-size" => "performance.write-behind-window-size",
+        "performance-io-thread-count" => "performance.io-thread-count",
+        "performance-flush-behind" => "performance.flush-behind",
+        "performance-cache-max-file-size" => "performance.cache-max-file-size",
+        "performance-cache-min-file-size" => "performance.cache-min-file-size",
+        "performance-cache-refresh-timeout" => "performance.cache-refresh-timeout",
+        "performance-cache-size" => "performance.cache-size",
+        "performance-readdir-ahead" => "performance.readdir-ahead",
+        "performance-parallel-readdir" => "performance.parallel-readdir",
+        "performance-readdir-cache-limit" => "performance.rda-cache-limit",
+        "server-allow-insecure" => "server.allow-insecure",
+        "server-grace-timeout" => "server.grace-timeout",
+        "server-statedump-path" => "server.statedump-path",
+        "storage-health-check-interval" => "storage.health-check-interval",
+        key => key,
+    }
+}
+
+/// The keys of every typed GlusterOption variant, ie: everything but
+/// GlusterOption::Unknown
+pub fn typed_option_keys() -> Vec<String> {
+    typed_option_examples()
+        .iter()
+        .map(GlusterOption::key)
+        .collect()
+}
+
+// One value of each typed variant.  Every variant has to be listed here for
+// typed_option_keys() to know its key.
+fn typed_option_examples() -> Vec<GlusterOption> {
+    vec![
+        GlusterOption::AuthAllow("192.168.1.*".to_string()),
+        GlusterOption::AuthReject("192.168.2.*".to_string()),
+        GlusterOption::ClientGraceTimeout(10),
+        GlusterOption::ClusterSelfHealWindowSize(16),
+        GlusterOption::ClientSsl(Toggle::On),
+        GlusterOption::ClusterDataSelfHealAlgorithm(SelfHealAlgorithm::Diff),
+        GlusterOption::ClusterMinFreeDisk(10),
+        GlusterOption::ClusterStripeBlockSize(131072),
+        GlusterOption::ClusterSelfHealDaemon(Toggle::Off),
+        GlusterOption::ClusterEnsureDurability(Toggle::On),
+        GlusterOption::DiagnosticsBrickLevel(log::Level::Warn),
+        GlusterOption::DiagnosticsClientLevel(log::Level::Trace),
+        GlusterOption::DiagnosticsFopSampleInterval(2),
+        GlusterOption::DiagnosticsFopSampleBufSize(65535),
+        GlusterOption::DiagnosticsCountFopHits(Toggle::On),
+        GlusterOption::DiagnosticsStatsDumpInterval(0),
+        GlusterOption::DiagnosticsStatsDnscacheTtlSec(86400),
+        GlusterOption::DiagnosticsLatencyMeasurement(Toggle::On),
+        GlusterOption::DiagnosticsDumpFdStats(Toggle::Off),
+        GlusterOption::FavoriteChildPolicy(SplitBrainPolicy::Disable),
+        GlusterOption::FeaturesReadOnly(Toggle::Off),
+        GlusterOption::FeaturesLockHeal(Toggle::On),
+        GlusterOption::FeaturesQuotaTimeout(5),
+        GlusterOption::GeoReplicationIndexing(Toggle::On),
+        GlusterOption::NetworkFrameTimeout(1800),
+        GlusterOption::NfsEnableIno32(Toggle::Off),
+        GlusterOption::NfsVolumeAccess(AccessMode::ReadOnly),
+        GlusterOption::NfsTrustedWrite(Toggle::Off),
+        GlusterOption::NfsTrustedSync(Toggle::Off),
+        GlusterOption::NfsExportDir("/data(192.168.1.0/24)".to_string()),
+        GlusterOption::NfsExportVolumes(Toggle::On),
+        GlusterOption::NfsRpcAuthUnix(Toggle::On),
+        GlusterOption::NfsRpcAuthNull(Toggle::On),
+        GlusterOption::NfsPortsInsecure(Toggle::Off),
+        GlusterOption::NfsAddrNamelookup(Toggle::Off),
+        GlusterOption::NfsRegisterWithPortmap(Toggle::On),
+        GlusterOption::NfsDisable(Toggle::On),
+        GlusterOption::PerformanceWriteBehindWindowSize(1048576),
+        GlusterOption::PerformanceIoThreadCount(16),
+        GlusterOption::PerformanceFlushBehind(Toggle::On),
+        GlusterOption::PerformanceCacheMaxFileSize(0),
+        GlusterOption::PerformanceCacheMinFileSize(0),
+        GlusterOption::PerformanceCacheRefreshTimeout(1),
+        GlusterOption::PerformanceCacheSize(33554432),
+        GlusterOption::PerformanceReadDirAhead(Toggle::On),
+        GlusterOption::PerformanceParallelReadDir(Toggle::Off),
+        GlusterOption::PerformanceReadDirAheadCacheLimit(10485760),
+        GlusterOption::ServerAllowInsecure(Toggle::On),
+        GlusterOption::ServerGraceTimeout(10),
+        GlusterOption::ServerSsl(Toggle::Off),
+        GlusterOption::ServerStatedumpPath(PathBuf::from("/var/run/gluster")),
+        GlusterOption::SslAllow("*".to_string()),
+        GlusterOption::SslCertificateDepth(1),
+        GlusterOption::SslCipherList("HIGH:!SSLv2".to_string()),
+        GlusterOption::StorageHealthCheckInterval(30),
+    ]
+}
+
+// Sizes are printed with a unit, eg: 32MB, and accepted as plain bytes
+fn parse_size(value: &str) -> Result<u64, GlusterError> {
+    let value = value.trim();
+    match u64::from_str(value) {
+        Ok(bytes) => Ok(bytes),
+        Err(_) => translate_to_bytes::<u64>(&value.to_ascii_uppercase())
+            .ok_or_else(|| GlusterError::parse_failure(value, "size")),
+    }
+}
+
+// glusterd's log levels are DEBUG, WARNING, ERROR, INFO, CRITICAL, NONE and
+// TRACE.  CRITICAL and NONE have no log::Level.
+fn parse_log_level(value: &str) -> Result<log::Level, GlusterError> {
+    match value.trim().to_ascii_uppercase().as_ref() {
+        "WARNING" => Ok(log::Level::Warn),
+        level => {
+            log::Level::from_str(level).map_err(|_| GlusterError::parse_failure(value, "log level"))
         }
     }
 }
 
+fn log_level_value(level: log::Level) -> String {
+    match level {
+        log::Level::Warn => "WARNING".to_string(),
+        level => level.to_string(),
+    }
+}
+
+#[test]
+fn test_gluster_option_round_trip() {
+    let mut options = typed_option_examples();
+    let mut keys = typed_option_keys();
+    keys.sort();
+    keys.dedup();
+    assert_eq!(keys.len(), options.len());
+    options.push(GlusterOption::Unknown(
+        "cluster.lookup-optimize".to_string(),
+        "on".to_string(),
+    ));
+    for option in options {
+        assert_eq!(
+            GlusterOption::from_str(&option.key(), option.value()).unwrap(),
+            option
+        );
+    }
+
+    // Values as glusterd prints them
+    let parse = |key: &str, value: &str| GlusterOption::from_str(key, value.to_string());
+    assert_eq!(
+        parse("performance.cache-size", "32MB").unwrap(),
+        GlusterOption::PerformanceCacheSize(33554432)
+    );
+    assert_eq!(
+        parse("cluster.min-free-disk", "10%").unwrap(),
+        GlusterOption::ClusterMinFreeDisk(10)
+    );
+    assert_eq!(
+        parse("diagnostics.brick-log-level", "WARNING").unwrap(),
+        GlusterOption::DiagnosticsBrickLevel(log::Level::Warn)
+    );
+    assert_eq!(
+        parse("nfs.disable", "True").unwrap(),
+        GlusterOption::NfsDisable(Toggle::On)
+    );
+    // The older dashed spelling
+    assert_eq!(
+        parse("nfs-disable", "off").unwrap(),
+        GlusterOption::NfsDisable(Toggle::Off)
+    );
+    assert!(parse("nfs.disable", "maybe").is_err());
+    assert!(parse("diagnostics.brick-log-level", "LOUD").is_err());
+    // Values glusterd accepts that the variants can't hold
+    assert_eq!(
+        parse("diagnostics.brick-log-level", "CRITICAL").unwrap(),
+        GlusterOption::Unknown(
+            "diagnostics.brick-log-level".to_string(),
+            "CRITICAL".to_string()
+        )
+    );
+    assert_eq!(
+        parse("diagnostics-client-log-level", "NONE").unwrap(),
+        GlusterOption::Unknown(
+            "diagnostics.client-log-level".to_string(),
+            "NONE".to_string()
+        )
+    );
+    assert_eq!(
+        parse("cluster.min-free-disk", "10GB").unwrap(),
+        GlusterOption::Unknown("cluster.min-free-disk".to_string(), "10GB".to_string())
+    );
+    assert_eq!(
+        parse("network.frame-timeout", "70000").unwrap(),
+        GlusterOption::Unknown("network.frame-timeout".to_string(), "70000".to_string())
+    );
+    assert!(parse("network.frame-timeout", "90000").is_err());
+}
+
 /// Custom error handling for the library
 #[derive(Debug)]
 pub enum GlusterError {
@@ -811,26 +1104,165 @@ pub enum GlusterError {
     ParseIntError(std::num::ParseIntError),
     RegexError(regex::Error),
     SerdeError(serde_json::Error),
+    XmlError(serde_xml_rs::Error),
+    /// The named volume doesn't exist
+    VolumeNotFound(String),
+    /// No peer in the cluster matches the given hostname
+    PeerNotFound(String),
+    /// A command ran but exited unsuccessfully.  exit_code is None if it was
+    /// killed by a signal.  kind says why, if the message was recognized.
+    /// For --xml commands exit_code is opRet and stderr is opErrstr.
+    CommandFailed {
+        argv: Vec<String>,
+        exit_code: Option<i32>,
+        stderr: String,
+        kind: CliFailure,
+    },
+    /// A command ran for longer than it was allowed to and was killed
+    Timeout {
+        argv: Vec<String>,
+        timeout: std::time::Duration,
+    },
+    /// A peer didn't reach the state that was waited for in time.
+    /// last_seen is the peer as it was last listed, None if it wasn't.
+    PeerWaitTimeout {
+        hostname: String,
+        last_seen: Option<Box<peer::Peer>>,
+        timeout: std::time::Duration,
+    },
+    /// The RPC server refused the call
+    RpcDenied(String),
+    /// The RPC server accepted the call but couldn't carry it out
+    RpcFailed(String),
+    /// An RPC reply couldn't be decoded
+    XdrDecode(String),
+    /// Output from Gluster couldn't be understood.  context describes what
+    /// was being parsed.
+    ParseFailure {
+        input: String,
+        context: String,
+    },
+    /// An argument was rejected before anything was run
+    InvalidArgument(String),
+    /// The Gluster version being talked to doesn't have this feature
+    Unsupported {
+        feature: version::Feature,
+        version: version::GlusterVersion,
+    },
+    /// Anything that doesn't fit one of the other variants
+    Other(String),
 }
 
 impl GlusterError {
     /// Create a new GlusterError with a String message
     pub fn new(err: String) -> GlusterError {
-        GlusterError::IoError(io::Error::new(std::io::ErrorKind::Other, err))
+        GlusterError::Other(err)
     }
 
-    /// Convert a GlusterError into a String representation.
-    pub fn to_string(&self) -> String {
+    pub(crate) fn parse_failure<I: Into<String>, C: Into<String>>(
+        input: I,
+        context: C,
+    ) -> GlusterError {
+        GlusterError::ParseFailure {
+            input: input.into(),
+            context: context.into(),
+        }
+    }
+}
+
+impl fmt::Display for GlusterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            GlusterError::IoError(ref err) => err.description().to_string(),
-            GlusterError::FromUtf8Error(ref err) => err.description().to_string(),
-            GlusterError::ParseError(ref err) => err.description().to_string(),
-            GlusterError::AddrParseError(ref err) => err.description().to_string(),
-            GlusterError::ParseIntError(ref err) => err.description().to_string(),
-            GlusterError::ParseBoolErr(ref err) => err.description().to_string(),
-            GlusterError::RegexError(ref err) => err.description().to_string(),
-            GlusterError::NoVolumesPresent => "No volumes present".to_string(),
-            GlusterError::SerdeError(ref err) => err.description().to_string(),
+            GlusterError::IoError(ref err) => err.fmt(f),
+            GlusterError::FromUtf8Error(ref err) => err.fmt(f),
+            GlusterError::ParseError(ref err) => err.fmt(f),
+            GlusterError::AddrParseError(ref err) => err.fmt(f),
+            GlusterError::ParseIntError(ref err) => err.fmt(f),
+            GlusterError::ParseBoolErr(ref err) => err.fmt(f),
+            GlusterError::RegexError(ref err) => err.fmt(f),
+            GlusterError::NoVolumesPresent => write!(f, "No volumes present"),
+            GlusterError::SerdeError(ref err) => err.fmt(f),
+            GlusterError::XmlError(ref err) => err.fmt(f),
+            GlusterError::VolumeNotFound(ref volume) => {
+                write!(f, "Volume {} does not exist", volume)
+            }
+            GlusterError::PeerNotFound(ref hostname) => {
+                write!(f, "Unable to find peer by hostname: {}", hostname)
+            }
+            GlusterError::CommandFailed {
+                ref argv,
+                exit_code,
+                ref stderr,
+                ..
+            } => match exit_code {
+                Some(code) => write!(
+                    f,
+                    "{} exited with {}: {}",
+                    argv.join(" "),
+                    code,
+                    stderr.trim()
+                ),
+                None => write!(
+                    f,
+                    "{} was killed by a signal: {}",
+                    argv.join(" "),
+                    stderr.trim()
+                ),
+            },
+            GlusterError::Timeout {
+                ref argv,
+                ref timeout,
+            } => write!(
+                f,
+                "Command timed out after {:?}: {}",
+                timeout,
+                argv.join(" ")
+            ),
+            GlusterError::PeerWaitTimeout {
+                ref hostname,
+                ref last_seen,
+                ref timeout,
+            } => match *last_seen {
+                Some(ref peer) => write!(
+                    f,
+                    "Gave up waiting on peer {} after {:?}.  Last seen: {:?}",
+                    hostname, timeout, peer
+                ),
+                None => write!(
+                    f,
+                    "Gave up waiting on peer {} after {:?}.  It was never listed",
+                    hostname, timeout
+                ),
+            },
+            GlusterError::RpcDenied(ref msg) => write!(f, "RPC call denied: {}", msg),
+            GlusterError::RpcFailed(ref msg) => write!(f, "RPC call failed: {}", msg),
+            GlusterError::XdrDecode(ref msg) => write!(f, "Unable to decode RPC reply: {}", msg),
+            GlusterError::ParseFailure {
+                ref input,
+                ref context,
+            } => write!(f, "Unable to parse {}: {}", context, input),
+            GlusterError::InvalidArgument(ref msg) => msg.fmt(f),
+            GlusterError::Unsupported { feature, version } => {
+                write!(f, "Gluster {} doesn't support {}", version, feature)
+            }
+            GlusterError::Other(ref msg) => msg.fmt(f),
+        }
+    }
+}
+
+impl Error for GlusterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            GlusterError::IoError(ref err) => Some(err),
+            GlusterError::FromUtf8Error(ref err) => Some(err),
+            GlusterError::ParseError(ref err) => Some(err),
+            GlusterError::AddrParseError(ref err) => Some(err),
+            GlusterError::ParseIntError(ref err) => Some(err),
+            GlusterError::ParseBoolErr(ref err) => Some(err),
+            GlusterError::RegexError(ref err) => Some(err),
+            GlusterError::SerdeError(ref err) => Some(err),
+            GlusterError::XmlError(ref err) => Some(err),
+            _ => None,
         }
     }
 }
@@ -883,13 +1315,19 @@ impl From<serde_json::Error> for GlusterError {
     }
 }
 
+impl From<serde_xml_rs::Error> for GlusterError {
+    fn from(err: serde_xml_rs::Error) -> GlusterError {
+        GlusterError::XmlError(err)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct BrickStatus {
     pub brick: Brick,
     pub tcp_port: u16,
     pub rdma_port: u16,
     pub online: bool,
-    pub pid: u16,
+    pub pid: u32,
 }
 
 impl Ord for BrickStatus {
@@ -912,121 +1350,120 @@ pub struct Quota {
     pub used: u64,
 }
 
-fn process_output(output: std::process::Output) -> Result<i32, GlusterError> {
-    let status = output.status;
-
-    if status.success() {
-        Ok(0)
-    } else {
-        Err(GlusterError::new(String::from_utf8(output.stderr)?))
+/// Turn a failed command into GlusterError::CommandFailed with its failure
+/// classified, or GlusterError::VolumeNotFound or PeerNotFound if that is
+/// what glusterd complained about.  Successful output is passed through untouched.
+fn process_output(
+    argv: Vec<String>,
+    output: std::process::Output,
+) -> Result<std::process::Output, GlusterError> {
+    if output.status.success() {
+        return Ok(output);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    // Some failures are printed to stdout instead
+    let kind = match CliFailure::classify(&stderr) {
+        CliFailure::Unknown => CliFailure::classify(&String::from_utf8_lossy(&output.stdout)),
+        kind => kind,
+    };
+    match kind {
+        CliFailure::VolumeNotFound { volume } => Err(GlusterError::VolumeNotFound(volume)),
+        CliFailure::PeerNotFound { host } => Err(GlusterError::PeerNotFound(host)),
+        kind => Err(GlusterError::CommandFailed {
+            argv,
+            exit_code: output.status.code(),
+            stderr,
+            kind,
+        }),
     }
 }
 
-// TODO: Change me to Result<std::process::Output, String>
-fn run_command<T>(
-    command: &str,
-    arg_list: &[T],
-    as_root: bool,
-    script_mode: bool,
-) -> std::process::Output
-where
-    T: AsRef<OsStr>,
-{
-    if as_root {
-        let mut cmd = std::process::Command::new("sudo");
-        cmd.arg(command);
-        if script_mode {
-            cmd.arg("--mode=script");
-        }
-        for arg in arg_list {
-            cmd.arg(&arg);
-        }
-        debug!("About to run command: {:?}", cmd);
-        cmd.output()
-            .unwrap_or_else(|e| panic!("failed to execute process: {} ", e))
-    } else {
-        let mut cmd = std::process::Command::new(command);
-        if script_mode {
-            cmd.arg("--mode=script");
-        }
-        for arg in arg_list {
-            cmd.arg(&arg);
-        }
-        debug!("About to run command: {:?}", cmd);
-        cmd.output()
-            .unwrap_or_else(|e| panic!("failed to execute process: {} ", e))
+// The --xml commands exit 0 and report failures in opRet, opErrno and
+// opErrstr.  They are classified like the errors of other commands, with
+// opRet as the exit code.
+fn xml_op_error(argv: &[String], ret: i32, errno: i32, err_str: Option<String>) -> GlusterError {
+    let err_str = err_str.unwrap_or_default();
+    debug!(
+        "{} failed with errno {}: {}",
+        argv.join(" "),
+        errno,
+        err_str
+    );
+    match CliFailure::classify(&err_str) {
+        CliFailure::VolumeNotFound { volume } => GlusterError::VolumeNotFound(volume),
+        CliFailure::PeerNotFound { host } => GlusterError::PeerNotFound(host),
+        kind => GlusterError::CommandFailed {
+            argv: argv.to_vec(),
+            exit_code: Some(ret),
+            stderr: err_str,
+            kind,
+        },
     }
 }
 
-// TODO: figure out a better way to do this.  This seems hacky
-/// Returns the local IPAddr address associated with this server
+/// Returns the local IPAddr address associated with this server: the
+/// address of the interface the default route goes out of
 /// # Failures
 /// Returns a GlusterError representing any failure that may have happened
 /// while trying to
 /// query this information.
 pub fn get_local_ip() -> Result<IpAddr, GlusterError> {
-    let mut default_route: Vec<String> = Vec::new();
-    default_route.push("route".to_string());
-    default_route.push("show".to_string());
-    default_route.push("0.0.0.0/0".to_string());
-
-    let cmd_output = run_command("ip", &default_route, false, false);
-    let default_route_stdout: String = String::from_utf8(cmd_output.stdout)?;
-
-    // default via 192.168.1.1 dev wlan0  proto static
-    let default_addr = default_route_stdout
-        .split_whitespace()
-        .collect::<Vec<&str>>();
-
-    let arg_list = vec![
-        "route".to_string(),
-        "get".to_string(),
-        default_addr[2].to_string(),
-    ];
-
-    let src_address_output = run_command("ip", &arg_list, false, false);
-    // 192.168.1.1 dev wlan0  src 192.168.1.7
-    let src_addr_output: String = String::from_utf8(src_address_output.stdout)?;
-    let local_ip = src_addr_output.split_whitespace().collect::<Vec<&str>>();
-
-    // Skip src in the capture
-    //let local_ip: Vec<&str> = src_address_output.as_str().split(" ").skip(1).collect();
-    let ip_addr = local_ip[4].trim().parse::<IpAddr>()?;
-
-    Ok(ip_addr)
+    GlusterClient::default().get_local_ip()
+}
+
+/// Returns every address of every interface on this server
+/// # Failures
+/// Returns GlusterError if the interfaces can't be listed
+pub fn get_local_ips() -> Result<Vec<IpAddr>, GlusterError> {
+    GlusterClient::default().get_local_ips()
 }
 
 /// Resolves a &str hostname into a ip address.
-pub fn resolve_to_ip(address: &str) -> Result<String, String> {
-    // Return dummy data if we're testing
-    if cfg!(test) {
-        return Ok("test_ip".to_string());
-    }
+/// # Failures
+/// Returns GlusterError if the name doesn't resolve to any address
+pub fn resolve_to_ip(address: &str) -> Result<IpAddr, GlusterError> {
+    GlusterClient::default().resolve_to_ip(address)
+}
 
-    if address == "localhost" {
-        let local_ip = get_local_ip().map_err(|e| e.to_string())?;
-        debug!(
-            "hostname is localhost.  Resolving to local ip {}",
-            &local_ip.to_string()
-        );
-        return Ok(local_ip.to_string());
+impl GlusterClient {
+    /// Returns the local IPAddr address associated with this server: the
+    /// address of the interface the default route goes out of
+    /// # Failures
+    /// Returns a GlusterError representing any failure that may have happened
+    /// while trying to
+    /// query this information.
+    pub fn get_local_ip(&self) -> Result<IpAddr, GlusterError> {
+        local::primary_address()
     }
 
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("+short".to_string());
-    // arg_list.push("-x".to_string());
-    arg_list.push(address.trim().to_string());
-    let output = run_command("dig", &arg_list, false, false);
-
-    let status = output.status;
+    /// Returns every address of every interface on this server
+    /// # Failures
+    /// Returns GlusterError if the interfaces can't be listed
+    pub fn get_local_ips(&self) -> Result<Vec<IpAddr>, GlusterError> {
+        local::local_addresses()
+    }
 
-    if status.success() {
-        let output_str = String::from_utf8(output.stdout).map_err(|e| e.to_string())?;
-        // Remove the trailing . and newline
-        let trimmed = output_str.trim().trim_right_matches('.');
-        Ok(trimmed.to_string())
-    } else {
-        Err(String::from_utf8(output.stderr).map_err(|e| e.to_string())?)
+    /// Resolves a &str hostname into a ip address.  localhost resolves to
+    /// the address other servers reach this one by.
+    /// # Failures
+    /// Returns GlusterError if the name doesn't resolve to any address
+    pub fn resolve_to_ip(&self, address: &str) -> Result<IpAddr, GlusterError> {
+        if address == "localhost" {
+            let local_ip = self.get_local_ip()?;
+            debug!(
+                "hostname is localhost.  Resolving to local ip {}",
+                &local_ip.to_string()
+            );
+            return Ok(local_ip);
+        }
+        let host = Host::new(address.trim());
+        match host.addresses(self.resolver())?.first() {
+            Some(ip) => Ok(*ip),
+            None => Err(GlusterError::new(format!(
+                "{} did not resolve to any address",
+                address
+            ))),
+        }
     }
 }
 
@@ -1092,15 +1529,33 @@ where
     }
 }
 
-/// Return all bricks that are being served locally in the volume
+/// Return all bricks that are being served locally in the volume.  A brick
+/// is local if its host resolves to any address of this server.
 pub fn get_local_bricks(volume: &str) -> Result<Vec<Brick>, GlusterError> {
-    let vol_info = volume_info(volume)?;
-    let local_ip = get_local_ip()?.to_string();
-    let bricks: Vec<Brick> = vol_info
-        .bricks
-        .iter()
-        .filter(|brick| brick.peer.hostname == local_ip)
-        .cloned()
-        .collect();
-    Ok(bricks)
+    GlusterClient::default().get_local_bricks(volume)
+}
+
+impl GlusterClient {
+    /// Return all bricks that are being served locally in the volume.  A
+    /// brick is local if its host resolves to any address of this server.
+    /// Bricks whose host doesn't resolve, eg: a decommissioned peer, are
+    /// taken not to be local.
+    pub fn get_local_bricks(&self, volume: &str) -> Result<Vec<Brick>, GlusterError> {
+        let vol_info = self.volume_info(volume)?;
+        let local_ips = self.get_local_ips()?;
+        let mut bricks: Vec<Brick> = Vec::new();
+        for brick in vol_info.bricks {
+            let addresses = match brick.peer.hostname.addresses(self.resolver()) {
+                Ok(addresses) => addresses,
+                Err(e) => {
+                    debug!("Skipping brick {}: {}", brick.to_string(), e);
+                    continue;
+                }
+            };
+            if addresses.iter().any(|ip| local_ips.contains(ip)) {
+                bricks.push(brick);
+            }
+        }
+        Ok(bricks)
+    }
 }
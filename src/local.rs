@@ -0,0 +1,172 @@
+//! Discovering the addresses of this server without running any commands.
+//!
+//! IPv6 addresses and the routing table come from /proc.  /proc has no list
+//! of IPv4 addresses per interface so those come from getifaddrs(3).
+use std::ffi::CStr;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ptr;
+
+use super::GlusterError;
+
+// The route is usable
+const RTF_UP: u32 = 0x0001;
+
+/// Every address of every interface on this server, including loopback
+/// # Failures
+/// Returns GlusterError if the interfaces can't be listed
+pub fn local_addresses() -> Result<Vec<IpAddr>, GlusterError> {
+    Ok(interface_addresses()?
+        .into_iter()
+        .map(|(_, ip)| ip)
+        .collect())
+}
+
+/// The address other servers most likely reach this one by: the first
+/// address of the interface the default route goes out of.  Without a
+/// default route it is the first address that is neither loopback nor link
+/// local.
+/// # Failures
+/// Returns GlusterError if there is no such address
+pub fn primary_address() -> Result<IpAddr, GlusterError> {
+    let addresses = interface_addresses()?;
+    if let Some(iface) = default_route_interface(&read_proc("/proc/net/route")?) {
+        // Prefer IPv4 since that is what the default route is for
+        let mut on_iface = addresses.iter().filter(|a| a.0 == iface);
+        if let Some(&(_, ip)) = on_iface.clone().find(|a| a.1.is_ipv4()).or(on_iface.next()) {
+            return Ok(ip);
+        }
+    }
+    addresses
+        .iter()
+        .map(|&(_, ip)| ip)
+        .find(|ip| !ip.is_loopback() && !is_link_local(ip))
+        .ok_or_else(|| GlusterError::new("No local address other servers can reach".to_string()))
+}
+
+fn is_link_local(ip: &IpAddr) -> bool {
+    match *ip {
+        IpAddr::V4(ref v4) => v4.is_link_local(),
+        IpAddr::V6(ref v6) => v6.segments()[0] & 0xffc0 == 0xfe80,
+    }
+}
+
+// A missing file means the kernel has nothing to report, eg: IPv6 disabled
+fn read_proc(path: &str) -> Result<String, GlusterError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(GlusterError::from(e)),
+    }
+}
+
+// Each interface name with one of its addresses
+fn interface_addresses() -> Result<Vec<(String, IpAddr)>, GlusterError> {
+    let mut addresses = interface_ipv4()?;
+    addresses.extend(parse_if_inet6(&read_proc("/proc/net/if_inet6")?)?);
+    Ok(addresses)
+}
+
+fn interface_ipv4() -> Result<Vec<(String, IpAddr)>, GlusterError> {
+    let mut addresses: Vec<(String, IpAddr)> = Vec::new();
+    let mut ifaddrs: *mut libc::ifaddrs = ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut ifaddrs) } != 0 {
+        return Err(GlusterError::from(io::Error::last_os_error()));
+    }
+    let mut cursor = ifaddrs;
+    while !cursor.is_null() {
+        let ifaddr = unsafe { &*cursor };
+        cursor = ifaddr.ifa_next;
+        if ifaddr.ifa_addr.is_null()
+            || i32::from(unsafe { (*ifaddr.ifa_addr).sa_family }) != libc::AF_INET
+        {
+            continue;
+        }
+        let sin = unsafe { &*(ifaddr.ifa_addr as *const libc::sockaddr_in) };
+        let name = unsafe { CStr::from_ptr(ifaddr.ifa_name) };
+        addresses.push((
+            name.to_string_lossy().into_owned(),
+            IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr))),
+        ));
+    }
+    unsafe { libc::freeifaddrs(ifaddrs) };
+    Ok(addresses)
+}
+
+// /proc/net/if_inet6 has one address per line:
+// fd000000000000000000000000000002 04 40 00 82     eth0
+// address, interface index, prefix length, scope, flags and interface name
+fn parse_if_inet6(contents: &str) -> Result<Vec<(String, IpAddr)>, GlusterError> {
+    let mut addresses: Vec<(String, IpAddr)> = Vec::new();
+    for line in contents.lines() {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        if columns.len() < 6 || columns[0].len() != 32 {
+            return Err(GlusterError::parse_failure(line, "/proc/net/if_inet6"));
+        }
+        let mut segments = [0u16; 8];
+        for (i, segment) in segments.iter_mut().enumerate() {
+            *segment = u16::from_str_radix(&columns[0][i * 4..i * 4 + 4], 16)?;
+        }
+        addresses.push((columns[5].to_string(), IpAddr::V6(Ipv6Addr::from(segments))));
+    }
+    Ok(addresses)
+}
+
+// The interface of the usable default route with the lowest metric.
+// /proc/net/route has a header line and then one route per line:
+// Iface Destination Gateway Flags RefCnt Use Metric Mask MTU Window IRTT
+fn default_route_interface(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            if columns.len() < 8 || columns[1] != "00000000" || columns[7] != "00000000" {
+                return None;
+            }
+            let flags = u32::from_str_radix(columns[3], 16).ok()?;
+            let metric: u32 = columns[6].parse().ok()?;
+            if flags & RTF_UP == 0 {
+                return None;
+            }
+            Some((metric, columns[0].to_string()))
+        })
+        .min()
+        .map(|(_, iface)| iface)
+}
+
+#[test]
+fn test_parse_proc_net() {
+    let header =
+        "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n";
+    let route = format!(
+        "{}wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0\n\
+         eth0\t00000000\t010200C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+         eth0\t000200C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n",
+        header
+    );
+    assert_eq!(default_route_interface(&route), Some("eth0".to_string()));
+    // No default route at all
+    let route = format!(
+        "{}eth0\t000200C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n",
+        header
+    );
+    assert_eq!(default_route_interface(&route), None);
+    assert_eq!(default_route_interface(""), None);
+
+    let if_inet6 = "fd000000000000000000000000000002 04 40 00 82     eth0\n\
+                    00000000000000000000000000000001 01 80 10 80       lo\n\
+                    fe8000000000000000fc00fffe000001 04 40 20 80     eth0\n";
+    let addresses = parse_if_inet6(if_inet6).unwrap();
+    assert_eq!(
+        addresses,
+        vec![
+            ("eth0".to_string(), "fd00::2".parse().unwrap()),
+            ("lo".to_string(), "::1".parse().unwrap()),
+            ("eth0".to_string(), "fe80::fc:ff:fe00:1".parse().unwrap()),
+        ]
+    );
+    assert!(is_link_local(&addresses[2].1));
+    assert!(parse_if_inet6("fd00 04 40 00 82 eth0").is_err());
+}
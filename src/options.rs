@@ -0,0 +1,610 @@
+//! Checking volume option values before they are handed to glusterd.
+//!
+//! glusterd only rejects a bad value while staging `gluster volume set`,
+//! after a round trip through every peer.  The constraints here catch the
+//! common mistakes up front.  They are looked up by option key and checked
+//! against the value as it would be passed to the CLI, so they apply to
+//! GlusterOption::Unknown options too.
+//!
+//! GlusterOption only has variants for a subset of the options glusterd
+//! knows.  The OptionCatalog lists all of them as `gluster volume set
+//! help-xml` describes them, so any of them can be set with a value of the
+//! right type.
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::IpAddr;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{parse_size, typed_option_keys, GlusterClient, GlusterError, GlusterOption, Toggle};
+use serde_xml_rs;
+
+/// What a valid value of an option looks like
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Constraint {
+    /// A whole number from min to max inclusive.  Sizes like 4MB are taken
+    /// as that many bytes.
+    Range(i64, i64),
+    /// One of these words
+    OneOf(&'static [&'static str]),
+    /// A comma separated list of addresses or host names, which may
+    /// contain * wildcards, eg: 192.168.1.*,client-*.example.com
+    AddressPatterns,
+    /// A directory that exists on this server
+    ExistingDirectory,
+    /// A percentage like 10% or a size like 10GB
+    PercentOrSize,
+}
+
+impl Constraint {
+    /// Why `value` doesn't satisfy the constraint, None if it does
+    pub fn check(&self, value: &str) -> Option<String> {
+        let value = value.trim();
+        match *self {
+            Constraint::Range(min, max) => match parse_number(value) {
+                Some(n) if n >= min && n <= max => None,
+                _ => Some(format!("must be a number from {} to {}", min, max)),
+            },
+            Constraint::OneOf(words) => {
+                if words.iter().any(|w| w.eq_ignore_ascii_case(value)) {
+                    None
+                } else {
+                    Some(format!("must be one of {}", words.join(", ")))
+                }
+            }
+            Constraint::AddressPatterns => value
+                .split(',')
+                .find(|pattern| !is_address_pattern(pattern.trim()))
+                .map(|pattern| format!("{:?} is not an address or host name", pattern)),
+            Constraint::ExistingDirectory => {
+                if Path::new(value).is_dir() {
+                    None
+                } else {
+                    Some("must be an existing directory".to_string())
+                }
+            }
+            Constraint::PercentOrSize => {
+                let valid = match value.strip_suffix('%') {
+                    Some(percent) => f64::from_str(percent.trim())
+                        .map(|n| (0.0..=100.0).contains(&n))
+                        .unwrap_or(false),
+                    None => parse_size(value).is_ok(),
+                };
+                if valid {
+                    None
+                } else {
+                    Some("must be a percentage from 0% to 100% or a size".to_string())
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Constraint::Range(min, max) => write!(f, "{}-{}", min, max),
+            Constraint::OneOf(words) => f.write_str(&words.join("|")),
+            Constraint::AddressPatterns => f.write_str("address patterns"),
+            Constraint::ExistingDirectory => f.write_str("existing directory"),
+            Constraint::PercentOrSize => f.write_str("percentage or size"),
+        }
+    }
+}
+
+const LOG_LEVELS: &[&str] = &[
+    "DEBUG", "WARNING", "ERROR", "INFO", "CRITICAL", "NONE", "TRACE",
+];
+
+// The limits glusterd's translators declare for each option
+const CONSTRAINTS: &[(&str, Constraint)] = &[
+    ("auth.allow", Constraint::AddressPatterns),
+    ("auth.reject", Constraint::AddressPatterns),
+    ("client.grace-timeout", Constraint::Range(10, 1800)),
+    (
+        "cluster.data-self-heal-algorithm",
+        Constraint::OneOf(&["full", "diff", "reset"]),
+    ),
+    (
+        "cluster.favorite-child-policy",
+        Constraint::OneOf(&["none", "ctime", "mtime", "size", "majority"]),
+    ),
+    ("cluster.min-free-disk", Constraint::PercentOrSize),
+    ("cluster.self-heal-window-size", Constraint::Range(0, 1025)),
+    ("diagnostics.brick-log-level", Constraint::OneOf(LOG_LEVELS)),
+    (
+        "diagnostics.client-log-level",
+        Constraint::OneOf(LOG_LEVELS),
+    ),
+    ("network.frame-timeout", Constraint::Range(0, 86400)),
+    (
+        "nfs.volume-access",
+        Constraint::OneOf(&["read-only", "read-write"]),
+    ),
+    (
+        "performance.cache-refresh-timeout",
+        Constraint::Range(0, 60),
+    ),
+    ("performance.io-thread-count", Constraint::Range(1, 64)),
+    (
+        "performance.write-behind-window-size",
+        Constraint::Range(512 * 1024, 1024 * 1024 * 1024),
+    ),
+    ("server.grace-timeout", Constraint::Range(10, 1800)),
+    ("server.statedump-path", Constraint::ExistingDirectory),
+    (
+        "transport.address-family",
+        Constraint::OneOf(&["inet", "inet6"]),
+    ),
+];
+
+/// The constraint on the option `key`, if there is one
+pub fn constraint(key: &str) -> Option<Constraint> {
+    CONSTRAINTS.iter().find(|c| c.0 == key).map(|c| c.1)
+}
+
+// A whole number, or a size with a unit as glusterd accepts for numeric
+// options
+fn parse_number(value: &str) -> Option<i64> {
+    match i64::from_str(value) {
+        Ok(n) => Some(n),
+        Err(_) => parse_size(value).ok().and_then(|n| i64::try_from(n).ok()),
+    }
+}
+
+// An IP address, a CIDR range, or an IPv4 address or host name with *
+// wildcards
+fn is_address_pattern(pattern: &str) -> bool {
+    if pattern.is_empty() {
+        return false;
+    }
+    if let Some((addr, prefix)) = pattern.split_once('/') {
+        return match (IpAddr::from_str(addr), u8::from_str(prefix)) {
+            (Ok(IpAddr::V4(_)), Ok(prefix)) => prefix <= 32,
+            (Ok(IpAddr::V6(_)), Ok(prefix)) => prefix <= 128,
+            _ => false,
+        };
+    }
+    if pattern.contains(':') {
+        return IpAddr::from_str(pattern).is_ok();
+    }
+    if pattern
+        .chars()
+        .all(|c| c.is_ascii_digit() || c == '.' || c == '*')
+    {
+        let octets: Vec<&str> = pattern.split('.').collect();
+        return octets.len() <= 4 && octets.iter().all(|o| *o == "*" || u8::from_str(o).is_ok());
+    }
+    pattern
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '*')
+}
+
+impl GlusterOption {
+    /// Check the value against the known constraints for this option.
+    /// Options without constraints are always valid.
+    /// # Failures
+    /// Returns GlusterError::InvalidArgument saying what is wrong
+    pub fn validate(&self) -> Result<(), GlusterError> {
+        let key = self.key();
+        let value = self.value();
+        match constraint(&key).and_then(|c| c.check(&value)) {
+            Some(reason) => Err(GlusterError::InvalidArgument(format!(
+                "Invalid value {:?} for {}: {}",
+                value, key, reason
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Validate every option, reporting all the invalid ones at once
+/// # Failures
+/// Returns GlusterError::InvalidArgument with one line per invalid option
+pub(crate) fn validate_all(settings: &[GlusterOption]) -> Result<(), GlusterError> {
+    let invalid: Vec<String> = settings
+        .iter()
+        .filter_map(|gluster_opt| gluster_opt.validate().err())
+        .map(|e| e.to_string())
+        .collect();
+    if invalid.is_empty() {
+        Ok(())
+    } else {
+        Err(GlusterError::InvalidArgument(invalid.join("\n")))
+    }
+}
+
+/// The kind of value an option takes.  glusterd doesn't say, so it is
+/// guessed from the option's default value.  The guess is only trusted for
+/// numbers: an option with an on/off default may take other words too, eg:
+/// cluster.lookup-unhashed takes auto, and cluster.min-free-disk takes a
+/// size as well as a percentage.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OptionType {
+    /// on or off, going by its default
+    Bool,
+    /// A whole number
+    Int,
+    /// A number of bytes, which may have a unit, eg: 32MB
+    Size,
+    /// A percentage, eg: 10%, going by its default
+    Percent,
+    /// Anything else, including options without a default
+    String,
+}
+
+impl OptionType {
+    fn from_default(default: &str) -> OptionType {
+        let default = default.trim();
+        if default.is_empty() {
+            OptionType::String
+        } else if Toggle::from_str(default).is_ok() && i64::from_str(default).is_err() {
+            OptionType::Bool
+        } else if i64::from_str(default).is_ok() {
+            OptionType::Int
+        } else if default
+            .strip_suffix('%')
+            .is_some_and(|n| f64::from_str(n).is_ok())
+        {
+            OptionType::Percent
+        } else if default.starts_with(|c: char| c.is_ascii_digit()) && parse_size(default).is_ok() {
+            OptionType::Size
+        } else {
+            OptionType::String
+        }
+    }
+
+    /// Why `value` isn't of this type, None if it is.  Only Int and Size
+    /// reject anything, see OptionType.
+    pub fn check(&self, value: &str) -> Option<String> {
+        let value = value.trim();
+        let ok = match *self {
+            OptionType::Int => parse_number(value).is_some(),
+            OptionType::Size => parse_size(value).is_ok(),
+            OptionType::Bool | OptionType::Percent | OptionType::String => true,
+        };
+        if ok {
+            None
+        } else {
+            Some(format!("must be {}", self))
+        }
+    }
+}
+
+impl fmt::Display for OptionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            OptionType::Bool => "on or off",
+            OptionType::Int => "a whole number",
+            OptionType::Size => "a size, eg: 32MB",
+            OptionType::Percent => "a percentage",
+            OptionType::String => "a string",
+        })
+    }
+}
+
+/// One option from `gluster volume set help-xml`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CatalogEntry {
+    pub name: String,
+    /// None if glusterd has no default, which it prints as "(null)"
+    pub default_value: Option<String>,
+    pub description: String,
+    pub option_type: OptionType,
+}
+
+/// Every volume option the gluster CLI knows about, by key
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OptionCatalog {
+    entries: BTreeMap<String, CatalogEntry>,
+}
+
+impl OptionCatalog {
+    /// The entry for the option `key`, eg: "cluster.shd-max-threads"
+    pub fn get(&self, key: &str) -> Option<&CatalogEntry> {
+        self.entries.get(key)
+    }
+
+    /// All the entries sorted by key
+    pub fn iter(&self) -> impl Iterator<Item = &CatalogEntry> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Build the option `key` with `value` after checking that the key is
+    /// in the catalog and the value is of its type.  Keys GlusterOption has
+    /// a variant for come back as that variant, the rest as
+    /// GlusterOption::Unknown.
+    /// # Failures
+    /// Returns GlusterError::InvalidArgument if the key isn't catalogued or
+    /// the value is invalid
+    pub fn option(&self, key: &str, value: &str) -> Result<GlusterOption, GlusterError> {
+        let key = key.trim();
+        let entry = self.get(key).ok_or_else(|| {
+            GlusterError::InvalidArgument(format!("Unknown volume option {}", key))
+        })?;
+        if let Some(reason) = entry.option_type.check(value) {
+            return Err(GlusterError::InvalidArgument(format!(
+                "Invalid value {:?} for {}: {}",
+                value, key, reason
+            )));
+        }
+        let option = GlusterOption::from_str(key, value.to_string()).map_err(|e| {
+            GlusterError::InvalidArgument(format!("Invalid value {:?} for {}: {}", value, key, e))
+        })?;
+        option.validate()?;
+        Ok(option)
+    }
+
+    /// The keys of typed GlusterOption variants that aren't in the
+    /// catalog.  Those options have been removed or renamed in this Gluster
+    /// release and setting them will fail.
+    pub fn stale_keys(&self) -> Vec<String> {
+        typed_option_keys()
+            .into_iter()
+            .filter(|key| !self.entries.contains_key(key))
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VolumeSetHelpXml {
+    #[serde(rename = "option", alias = "volumeOption", default)]
+    option: Vec<OptionHelpXml>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OptionHelpXml {
+    name: String,
+    #[serde(rename = "defaultValue", default)]
+    default_value: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// Parse the output of `gluster volume set help-xml`
+/// # Failures
+/// Returns GlusterError if the XML isn't understood
+pub fn parse_option_catalog(output_str: &str) -> Result<OptionCatalog, GlusterError> {
+    let help: VolumeSetHelpXml = serde_xml_rs::from_str(output_str)?;
+    let entries = help
+        .option
+        .into_iter()
+        .map(|o| {
+            let default_value = o.default_value.trim();
+            let default_value = match default_value {
+                "(null)" | "" => None,
+                value => Some(value.to_string()),
+            };
+            let option_type = OptionType::from_default(default_value.as_deref().unwrap_or(""));
+            let name = o.name.trim().to_string();
+            let entry = CatalogEntry {
+                name: name.clone(),
+                default_value,
+                description: o.description.trim().to_string(),
+                option_type,
+            };
+            (name, entry)
+        })
+        .collect();
+    Ok(OptionCatalog { entries })
+}
+
+/// Every volume option the gluster CLI knows about
+/// # Failures
+/// Returns GlusterError if the command fails to run or its output isn't
+/// understood
+pub fn volume_option_catalog() -> Result<OptionCatalog, GlusterError> {
+    GlusterClient::default().volume_option_catalog()
+}
+
+/// Set any catalogued option on a volume
+/// # Failures
+/// Returns GlusterError::InvalidArgument if the key isn't catalogued or the
+/// value is invalid or GlusterError if the command fails to run
+pub fn volume_set_option(volume: &str, key: &str, value: &str) -> Result<(), GlusterError> {
+    GlusterClient::default().volume_set_option(volume, key, value)
+}
+
+impl GlusterClient {
+    /// Every volume option the gluster CLI knows about.  The CLI prints
+    /// this without asking glusterd so it describes the installed release.
+    /// # Failures
+    /// Returns GlusterError if the command fails to run or its output isn't
+    /// understood
+    pub fn volume_option_catalog(&self) -> Result<OptionCatalog, GlusterError> {
+        let output = self.gluster_checked(&["volume", "set", "help-xml"])?;
+        parse_option_catalog(&String::from_utf8(output.stdout)?)
+    }
+
+    /// Set any catalogued option on a volume, eg: cluster.shd-max-threads,
+    /// after checking the value against the catalog.  The catalog is
+    /// fetched on every call so use OptionCatalog::option and
+    /// volume_set_options to set several.
+    /// # Failures
+    /// Returns GlusterError::InvalidArgument if the key isn't catalogued or
+    /// the value is invalid or GlusterError if the command fails to run
+    pub fn volume_set_option(
+        &self,
+        volume: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), GlusterError> {
+        let option = self.volume_option_catalog()?.option(key, value)?;
+        self.volume_set_options(volume, &[option])?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_validate_options() {
+    use std::path::PathBuf;
+    use Toggle;
+
+    let valid = vec![
+        GlusterOption::AuthAllow("192.168.1.*,10.0.0.0/8,client-*.example.com".to_string()),
+        GlusterOption::AuthReject("fd00::1".to_string()),
+        GlusterOption::ClientGraceTimeout(10),
+        GlusterOption::ClusterMinFreeDisk(100),
+        GlusterOption::DiagnosticsBrickLevel(::log::Level::Warn),
+        GlusterOption::PerformanceWriteBehindWindowSize(1024 * 1024),
+        GlusterOption::ServerStatedumpPath(PathBuf::from("tests")),
+        GlusterOption::NfsDisable(Toggle::On),
+        GlusterOption::Unknown("transport.address-family".to_string(), "inet6".to_string()),
+        // glusterd takes sizes with units for numeric options
+        GlusterOption::Unknown(
+            "performance.write-behind-window-size".to_string(),
+            "4MB".to_string(),
+        ),
+        GlusterOption::Unknown("cluster.min-free-disk".to_string(), "10GB".to_string()),
+        GlusterOption::Unknown("cluster.min-free-disk".to_string(), "5%".to_string()),
+    ];
+    for option in valid {
+        assert!(option.validate().is_ok(), "{:?}", option);
+    }
+
+    let invalid = vec![
+        GlusterOption::AuthAllow("192.168.1.*, bad host".to_string()),
+        GlusterOption::AuthAllow("192.168.300.1".to_string()),
+        GlusterOption::AuthReject("10.0.0.0/33".to_string()),
+        GlusterOption::ClientGraceTimeout(5),
+        GlusterOption::ClusterSelfHealWindowSize(2048),
+        GlusterOption::ClusterMinFreeDisk(101),
+        GlusterOption::PerformanceIoThreadCount(0),
+        GlusterOption::ServerStatedumpPath(PathBuf::from("tests/no-such-dir")),
+        GlusterOption::Unknown("transport.address-family".to_string(), "unix".to_string()),
+        GlusterOption::Unknown(
+            "performance.write-behind-window-size".to_string(),
+            "4GB".to_string(),
+        ),
+        GlusterOption::Unknown("cluster.min-free-disk".to_string(), "110%".to_string()),
+        GlusterOption::Unknown("cluster.min-free-disk".to_string(), "lots".to_string()),
+    ];
+    for option in invalid {
+        assert!(option.validate().is_err(), "{:?}", option);
+    }
+    match GlusterOption::ClientGraceTimeout(5).validate() {
+        Err(GlusterError::InvalidArgument(ref msg)) => assert_eq!(
+            msg,
+            "Invalid value \"5\" for client.grace-timeout: must be a number from 10 to 1800"
+        ),
+        other => panic!("expected InvalidArgument, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_option_catalog() {
+    use executor::CannedExecutor;
+    use std::fs::File;
+    use std::io::Read;
+    use std::sync::Arc;
+
+    let mut f = File::open("tests/volume_set_help.xml").unwrap();
+    let mut s = String::new();
+    f.read_to_string(&mut s).unwrap();
+    let catalog = parse_option_catalog(&s).unwrap();
+    assert_eq!(catalog.len(), 57);
+
+    let shd = catalog.get("cluster.shd-max-threads").unwrap();
+    assert_eq!(shd.default_value, Some("1".to_string()));
+    assert_eq!(shd.option_type, OptionType::Int);
+    assert!(shd
+        .description
+        .starts_with("Maximum number of parallel heals"));
+    let types = |key: &str| catalog.get(key).unwrap().option_type;
+    assert_eq!(types("performance.client-io-threads"), OptionType::Bool);
+    assert_eq!(types("nfs.enable-ino32"), OptionType::Bool);
+    assert_eq!(types("performance.cache-size"), OptionType::Size);
+    assert_eq!(types("cluster.min-free-disk"), OptionType::Percent);
+    assert_eq!(types("transport.address-family"), OptionType::String);
+    assert_eq!(catalog.get("auth.reject").unwrap().default_value, None);
+    assert_eq!(types("auth.reject"), OptionType::String);
+
+    assert_eq!(
+        catalog.option("cluster.shd-max-threads", "4").unwrap(),
+        GlusterOption::Unknown("cluster.shd-max-threads".to_string(), "4".to_string())
+    );
+    assert_eq!(
+        catalog.option("nfs.disable", "off").unwrap(),
+        GlusterOption::NfsDisable(Toggle::Off)
+    );
+    assert_eq!(
+        catalog.option("performance.cache-size", "64MB").unwrap(),
+        GlusterOption::PerformanceCacheSize(64 * 1024 * 1024)
+    );
+    assert!(catalog.option("cluster.shd-max-threads", "lots").is_err());
+    assert!(catalog.option("performance.cache-size", "big").is_err());
+    // Only numbers are type checked.  Other guesses are too often wrong.
+    assert_eq!(types("cluster.lookup-unhashed"), OptionType::Bool);
+    assert!(catalog.option("cluster.lookup-unhashed", "auto").is_ok());
+    assert!(catalog.option("cluster.data-self-heal", "open").is_ok());
+    assert!(catalog.option("cluster.min-free-disk", "10GB").is_ok());
+    // The constraints still apply
+    assert!(catalog.option("cluster.min-free-disk", "110%").is_err());
+    assert!(catalog.option("transport.address-family", "unix").is_err());
+    match catalog.option("cluster.no-such-option", "on") {
+        Err(GlusterError::InvalidArgument(ref msg)) => {
+            assert_eq!(msg, "Unknown volume option cluster.no-such-option")
+        }
+        other => panic!("expected InvalidArgument, got {:?}", other),
+    }
+
+    let canned = Arc::new(CannedExecutor::new().respond(&["volume", "set", "help-xml"], 0, &s, ""));
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    client
+        .volume_set_option("test", "cluster.shd-max-threads", "4")
+        .unwrap();
+    assert!(client
+        .volume_set_option("test", "cluster.shd-max-threads", "four")
+        .is_err());
+    assert_eq!(
+        canned.calls(),
+        vec![
+            vec!["gluster", "--mode=script", "volume", "set", "help-xml"],
+            vec![
+                "gluster",
+                "--mode=script",
+                "volume",
+                "set",
+                "test",
+                "cluster.shd-max-threads",
+                "4",
+            ],
+            vec!["gluster", "--mode=script", "volume", "set", "help-xml"],
+        ]
+    );
+}
+
+#[test]
+fn test_typed_options_are_catalogued() {
+    use std::fs::File;
+    use std::io::Read;
+
+    // The help output is from a release without striping, lock healing or
+    // grace timeouts.  Their variants are kept for older releases.
+    let removed = [
+        "client.grace-timeout",
+        "cluster.stripe-block-size",
+        "features.lock-heal",
+        "server.grace-timeout",
+    ];
+    let mut f = File::open("tests/volume_set_help.xml").unwrap();
+    let mut s = String::new();
+    f.read_to_string(&mut s).unwrap();
+    let catalog = parse_option_catalog(&s).unwrap();
+    for key in catalog.stale_keys() {
+        assert!(
+            removed.contains(&key.as_str()),
+            "GlusterOption has a variant for {} which gluster doesn't know",
+            key
+        );
+    }
+}
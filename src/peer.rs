@@ -2,72 +2,50 @@ use std::cmp::Ord;
 use std::cmp::Ordering;
 use std::fmt;
 use std::net::IpAddr;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use super::{process_output, resolve_to_ip, run_command, GlusterError};
-use regex::Regex;
+use super::{xml_op_error, GlusterClient, GlusterError, Host};
 use uuid::Uuid;
 
-#[test]
-fn test_parse_peer_status() {
-    let test_result = vec![
-        Peer {
-            uuid: Uuid::parse_str("afbd338e-881b-4557-8764-52e259885ca3").unwrap(),
-            hostname: "10.0.3.207".to_string(),
-            status: State::PeerInCluster,
-        },
-        Peer {
-            uuid: Uuid::parse_str("fa3b031a-c4ef-43c5-892d-4b909bc5cd5d").unwrap(),
-            hostname: "10.0.3.208".to_string(),
-            status: State::PeerInCluster,
-        },
-        Peer {
-            uuid: Uuid::parse_str("5f45e89a-23c1-41dd-b0cd-fd9cf37f1520").unwrap(),
-            hostname: "10.0.3.209".to_string(),
-            status: State::PeerInCluster,
-        },
-    ];
-    let test_line = r#"Number of Peers: 3 Hostname: 10.0.3.207
-Uuid: afbd338e-881b-4557-8764-52e259885ca3 State: Peer in Cluster (Connected)
-Hostname: 10.0.3.208 Uuid: fa3b031a-c4ef-43c5-892d-4b909bc5cd5d
-State: Peer in Cluster (Connected) Hostname: 10.0.3.209
-Uuid: 5f45e89a-23c1-41dd-b0cd-fd9cf37f1520 State: Peer in Cluster (Connected)"#
-        .to_string();
-
-    // Expect a 3 peer result
-    let result = parse_peer_status(&test_line);
-    println!("Result: {:?}", result);
-    assert!(result.is_ok());
-
-    let result_unwrapped = result.unwrap();
-    assert_eq!(test_result, result_unwrapped);
-}
-
-/// A enum representing the possible States that a Peer can be in
+/// The states of glusterd's friend state machine.  This is how far a peer
+/// has got in joining (or leaving) the cluster and says nothing about whether
+/// it can be reached right now; that is Peer::connected.
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub enum State {
-    Connected,
-    Disconnected,
-    Unknown,
-
+    /// Waiting for the connection to a newly probed peer
     EstablishingConnection,
     ProbeSentToPeer,
     ProbeReceivedFromPeer,
+    /// Fully joined.  The only state a working peer should be in.
     PeerInCluster,
     AcceptedPeerRequest,
     SentAndReceivedPeerRequest,
+    /// The peer's volume configuration doesn't match the cluster's.  It won't
+    /// recover without intervention.
     PeerRejected,
     PeerDetachInProgress,
+    /// A probe was received and the protocol versions are being negotiated
+    ProtocolQuery,
     ConnectedToPeer,
     PeerIsConnectedAndAccepted,
     InvalidState,
+    /// This node.  glusterd keeps no friend state for itself.
+    Local,
+    /// A state this library doesn't know about
+    Unknown,
 }
 
 impl State {
-    /// Create a new State object from a &str
+    /// Create a new State object from a &str.  Text as `gluster peer status`
+    /// prints it, with the connection state in brackets, is accepted too.
     pub fn new(name: &str) -> State {
+        let name = match name.find('(') {
+            Some(i) => &name[..i],
+            None => name,
+        };
         match name.trim().to_ascii_lowercase().as_ref() {
-            "connected" => State::Connected,
-            "disconnected" => State::Disconnected,
             "establishing connection" => State::EstablishingConnection,
             "probe sent to peer" => State::ProbeSentToPeer,
             "probe received from peer" => State::ProbeReceivedFromPeer,
@@ -82,37 +60,130 @@ impl State {
             _ => State::Unknown,
         }
     }
-    /// Return a string representation of the State
-    pub fn to_string(self) -> String {
+
+    /// Create a State from glusterd's glusterd_friend_sm_state_t number as
+    /// given by `gluster peer status --xml` and the peer files
+    pub fn from_code(code: u32) -> State {
+        match code {
+            0 => State::EstablishingConnection,
+            1 => State::ProbeSentToPeer,
+            2 => State::ProbeReceivedFromPeer,
+            3 => State::PeerInCluster,
+            4 => State::AcceptedPeerRequest,
+            5 => State::SentAndReceivedPeerRequest,
+            6 => State::PeerRejected,
+            7 => State::PeerDetachInProgress,
+            8 => State::ProtocolQuery,
+            9 => State::ConnectedToPeer,
+            10 => State::PeerIsConnectedAndAccepted,
+            11 => State::InvalidState,
+            _ => State::Unknown,
+        }
+    }
+
+    /// Whether the peer is part of the cluster
+    pub fn is_in_cluster(self) -> bool {
+        self == State::PeerInCluster || self == State::Local
+    }
+
+    /// Whether the peer is on its way into or out of the cluster and will
+    /// move on by itself as long as it stays connected
+    pub fn is_transitional(self) -> bool {
         match self {
-            State::Connected => "Connected".to_string(),
-            State::Disconnected => "Disconnected".to_string(),
-            State::Unknown => "Unknown".to_string(),
-            State::EstablishingConnection => "establishing connection".to_string(),
-            State::ProbeSentToPeer => "probe sent to peer".to_string(),
-            State::ProbeReceivedFromPeer => "probe received from peer".to_string(),
-            State::PeerInCluster => "peer in cluster".to_string(),
-            State::AcceptedPeerRequest => "accepted peer request".to_string(),
-            State::SentAndReceivedPeerRequest => "sent and received peer request".to_string(),
-            State::PeerRejected => "peer rejected".to_string(),
-            State::PeerDetachInProgress => "peer detach in progress".to_string(),
-            State::ConnectedToPeer => "connected to peer".to_string(),
-            State::PeerIsConnectedAndAccepted => "peer is connected and accepted".to_string(),
-            State::InvalidState => "invalid state".to_string(),
+            State::EstablishingConnection
+            | State::ProbeSentToPeer
+            | State::ProbeReceivedFromPeer
+            | State::AcceptedPeerRequest
+            | State::SentAndReceivedPeerRequest
+            | State::PeerDetachInProgress
+            | State::ProtocolQuery
+            | State::ConnectedToPeer
+            | State::PeerIsConnectedAndAccepted => true,
+            State::PeerInCluster
+            | State::PeerRejected
+            | State::InvalidState
+            | State::Local
+            | State::Unknown => false,
         }
     }
 }
 
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            State::Local => "local",
+            State::Unknown => "Unknown",
+            State::EstablishingConnection => "establishing connection",
+            State::ProbeSentToPeer => "probe sent to peer",
+            State::ProbeReceivedFromPeer | State::ProtocolQuery => "probe received from peer",
+            State::PeerInCluster => "peer in cluster",
+            State::AcceptedPeerRequest => "accepted peer request",
+            State::SentAndReceivedPeerRequest => "sent and received peer request",
+            State::PeerRejected => "peer rejected",
+            State::PeerDetachInProgress => "peer detach in progress",
+            State::ConnectedToPeer => "connected to peer",
+            State::PeerIsConnectedAndAccepted => "peer is connected and accepted",
+            State::InvalidState => "invalid state",
+        })
+    }
+}
+
 /// A Gluster Peer.  A Peer is roughly equivalent to a server in Gluster.
 #[derive(Clone, Eq, PartialEq)]
 pub struct Peer {
     /// The unique identifer of this peer
     pub uuid: Uuid,
-    // TODO: Lets stay with ip addresses.
-    /// The hostname or IP address of the peer
-    pub hostname: String,
+    /// The hostname or IP address of the peer as glusterd knows it
+    pub hostname: Host,
+    /// Every name and address the peer is known by, starting with hostname.
+    /// A peer that was probed by more than one name or has more than one
+    /// network lists them all.
+    pub hostnames: Vec<Host>,
+    /// Whether glusterd has a connection to the peer right now
+    pub connected: bool,
+    /// glusterd's friend state machine state as a number, if reported
+    pub state_code: Option<u32>,
     ///  The current State of the peer
     pub status: State,
+    /// The state exactly as the CLI described it, eg: "Peer Rejected".  Empty
+    /// if the state didn't come from the CLI.
+    pub state_text: String,
+}
+
+impl Peer {
+    /// A peer known by a single name
+    pub fn new(uuid: Uuid, hostname: Host, status: State) -> Peer {
+        Peer {
+            uuid,
+            hostnames: vec![hostname.clone()],
+            hostname,
+            connected: false,
+            state_code: None,
+            status,
+            state_text: String::new(),
+        }
+    }
+
+    /// Whether the peer is known by `host`
+    pub fn is_known_as(&self, host: &Host) -> bool {
+        self.hostname == *host || self.hostnames.contains(host)
+    }
+
+    /// Whether the peer is connected and part of the cluster
+    pub fn is_healthy(&self) -> bool {
+        self.connected && self.status.is_in_cluster()
+    }
+
+    /// Whether the peer won't become healthy without someone stepping in:
+    /// it is disconnected, rejected or in a state glusterd shouldn't be in.
+    /// Connected peers that are still joining don't need attention.
+    pub fn needs_attention(&self) -> bool {
+        if self.connected {
+            !self.status.is_in_cluster() && !self.status.is_transitional()
+        } else {
+            true
+        }
+    }
 }
 
 impl Ord for Peer {
@@ -130,10 +201,15 @@ impl fmt::Debug for Peer {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "UUID: {} Hostname: {} Status: {}",
-            self.uuid.to_hyphenated().to_string(),
+            "UUID: {} Hostname: {} Status: {} ({})",
+            self.uuid.to_hyphenated(),
             self.hostname,
-            self.status.to_string()
+            self.status,
+            if self.connected {
+                "Connected"
+            } else {
+                "Disconnected"
+            }
         )
     }
 }
@@ -142,77 +218,204 @@ impl fmt::Debug for Peer {
 /// # Failures
 /// Returns GlusterError if the peer could not be found
 pub fn get_peer(hostname: &str) -> Result<Peer, GlusterError> {
-    // Return dummy data if we're testing
-    if cfg!(test) {
-        return Ok(Peer {
-            uuid: Uuid::parse_str("78f68270-201a-4d8a-bad3-7cded6e6b7d8").unwrap(),
-            hostname: "test_ip".to_string(),
-            status: State::Connected,
-        });
-    }
-    let peer_list = peer_list()?;
+    GlusterClient::default().get_peer(hostname)
+}
 
-    for peer in peer_list {
-        if peer.hostname == *hostname {
-            debug!("Found peer: {:?}", peer);
-            return Ok(peer.clone());
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct PeerStatusCliXml {
+    #[serde(rename = "opRet")]
+    pub ret: i32,
+    #[serde(rename = "opErrno")]
+    pub errno: i32,
+    #[serde(rename = "opErrstr")]
+    pub err_str: Option<String>,
+    #[serde(rename = "peerStatus", default)]
+    pub peer_status: XmlPeerStatus,
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct XmlPeerStatus {
+    #[serde(default)]
+    pub peer: Vec<PeerXml>,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct PeerXml {
+    pub uuid: Uuid,
+    pub hostname: String,
+    #[serde(default)]
+    pub hostnames: XmlHostnames,
+    pub connected: String,
+    /// Missing for this node in `gluster pool list`
+    #[serde(default)]
+    pub state: String,
+    #[serde(rename = "stateStr", default)]
+    pub state_str: String,
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct XmlHostnames {
+    #[serde(default)]
+    pub hostname: Vec<String>,
+}
+
+impl PeerXml {
+    /// Convert into a Peer.  The hostname comes first in its hostnames.
+    pub fn into_peer(self) -> Result<Peer, GlusterError> {
+        let hostname = Host::new(self.hostname.trim());
+        let mut hostnames = vec![hostname.clone()];
+        for name in self.hostnames.hostname {
+            let name = Host::new(name.trim());
+            if !hostnames.contains(&name) {
+                hostnames.push(name);
+            }
         }
+        let connected = self.connected.trim() == "1";
+        let state_code = match self.state.trim() {
+            "" => None,
+            code => Some(u32::from_str(code)?),
+        };
+        // Only this node is listed without a state.  The number tells apart
+        // states that glusterd describes with the same text.
+        let status = match state_code {
+            Some(code) => State::from_code(code),
+            None => State::Local,
+        };
+        Ok(Peer {
+            uuid: self.uuid,
+            hostname,
+            hostnames,
+            connected,
+            state_code,
+            status,
+            state_text: self.state_str.trim().to_string(),
+        })
     }
-    Err(GlusterError::new(format!(
-        "Unable to find peer by hostname: {}",
-        hostname
-    )))
 }
 
-fn parse_peer_status(line: &str) -> Result<Vec<Peer>, GlusterError> {
-    let mut peers: Vec<Peer> = Vec::new();
-
-    // TODO: It's either this or some kinda crazy looping or batching
-    let regex_str = r#"Hostname:\s+(?P<hostname>[a-zA-Z0-9.]+)\s+
-Uuid:\s+(?P<uuid>\w+-\w+-\w+-\w+-\w+)\s+
-State:\s+(?P<state_detail>[a-zA-z ]+)\s+\((?P<state>\w+)\)"#;
-    let peer_regex = Regex::new(&regex_str.replace("\n", ""))?;
-    for cap in peer_regex.captures_iter(line) {
-        let hostname = cap
-            .name("hostname")
-            .ok_or_else(|| GlusterError::new(format!("Invalid hostname for peer: {}", line)))?;
-
-        let uuid = cap
-            .name("uuid")
-            .ok_or_else(|| GlusterError::new(format!("Invalid uuid for peer: {}", line)))?;
-        let uuid_parsed = Uuid::parse_str(uuid.as_str())?;
-        let state_details = cap
-            .name("state_detail")
-            .ok_or_else(|| GlusterError::new(format!("Invalid state for peer: {}", line)))?;
-
-        // Translate back into an IP address if needed
-        let check_for_ip = hostname.as_str().parse::<IpAddr>();
-
-        if check_for_ip.is_err() {
-            // It's a hostname so lets resolve it
-            match resolve_to_ip(hostname.as_str()) {
-                Ok(ip_addr) => {
-                    peers.push(Peer {
-                        uuid: uuid_parsed,
-                        hostname: ip_addr,
-                        status: State::new(state_details.as_str()),
-                    });
-                    continue;
-                }
-                Err(e) => {
-                    return Err(GlusterError::new(e.to_string()));
-                }
-            };
-        } else {
-            // It's an IP address so lets use it
-            peers.push(Peer {
-                uuid: uuid_parsed,
-                hostname: hostname.as_str().to_string(),
-                status: State::new(state_details.as_str()),
-            });
+/// Parse the output of `gluster peer status --xml` or `gluster pool list --xml`
+fn parse_peer_status_xml(output_str: &str, argv: &[String]) -> Result<Vec<Peer>, GlusterError> {
+    let cli_output: PeerStatusCliXml = serde_xml_rs::from_str(output_str)?;
+    if cli_output.ret != 0 {
+        return Err(xml_op_error(
+            argv,
+            cli_output.ret,
+            cli_output.errno,
+            cli_output.err_str,
+        ));
+    }
+    cli_output
+        .peer_status
+        .peer
+        .into_iter()
+        .map(PeerXml::into_peer)
+        .collect()
+}
+
+#[test]
+fn test_parse_peer_status_xml() {
+    use std::fs::File;
+    use std::io::Read;
+
+    let test_data = {
+        let mut f = File::open("tests/peer_status.xml").unwrap();
+        let mut s = String::new();
+        f.read_to_string(&mut s).unwrap();
+        s
+    };
+    let peers = parse_peer_status_xml(&test_data, &[]).unwrap();
+    assert_eq!(peers.len(), 3);
+    assert_eq!(
+        peers[0],
+        Peer {
+            uuid: Uuid::parse_str("afbd338e-881b-4557-8764-52e259885ca3").unwrap(),
+            hostname: Host::new("10.0.3.207"),
+            hostnames: vec![
+                Host::new("10.0.3.207"),
+                Host::new("gluster-2.example.com"),
+                Host::new("192.168.10.2"),
+            ],
+            connected: true,
+            state_code: Some(3),
+            status: State::PeerInCluster,
+            state_text: "Peer in Cluster".to_string(),
         }
+    );
+    assert!(peers[0].is_healthy());
+    assert!(!peers[0].needs_attention());
+    // Multi-homed peers are recognized by any of their names
+    assert!(peers[0].is_known_as(&Host::new("192.168.10.2")));
+    assert!(!peers[0].is_known_as(&Host::new("gluster-3.example.com")));
+
+    assert_eq!(peers[1].hostname, "gluster-3.example.com");
+    assert!(!peers[1].connected);
+    assert_eq!(peers[1].status, State::PeerInCluster);
+    // In the cluster but unreachable
+    assert!(!peers[1].is_healthy());
+    assert!(peers[1].needs_attention());
+    assert_eq!(peers[2].state_code, Some(6));
+    assert_eq!(peers[2].status, State::PeerRejected);
+    assert_eq!(peers[2].state_text, "Peer Rejected");
+    assert!(peers[2].connected);
+    assert!(peers[2].needs_attention());
+
+    match parse_peer_status_xml(
+        "<cliOutput><opRet>-1</opRet><opErrno>0</opErrno>\
+         <opErrstr>node5 is not part of cluster</opErrstr></cliOutput>",
+        &[],
+    ) {
+        Err(GlusterError::PeerNotFound(ref host)) => assert_eq!(host, "node5"),
+        other => panic!("expected PeerNotFound, got {:?}", other),
     }
-    Ok(peers)
+
+    let test_data = {
+        let mut f = File::open("tests/pool_list.xml").unwrap();
+        let mut s = String::new();
+        f.read_to_string(&mut s).unwrap();
+        s
+    };
+    let peers = parse_peer_status_xml(&test_data, &[]).unwrap();
+    assert_eq!(peers.len(), 2);
+    assert_eq!(peers[1].hostname, "localhost");
+    assert_eq!(peers[1].hostnames, vec![Host::new("localhost")]);
+    assert_eq!(peers[1].state_code, None);
+    assert_eq!(peers[1].status, State::Local);
+    assert!(peers[1].is_healthy());
+
+    // The text form of `gluster peer status`.  Only the number tells these
+    // two apart.
+    assert_eq!(State::new("Peer Rejected (Connected)"), State::PeerRejected);
+    assert_eq!(
+        State::from_code(2).to_string(),
+        State::from_code(8).to_string()
+    );
+    assert_eq!(State::from_code(8), State::ProtocolQuery);
+
+    // A node without peers
+    let peers = parse_peer_status_xml(
+        "<cliOutput><opRet>0</opRet><opErrno>0</opErrno><opErrstr/>\
+         <peerStatus/></cliOutput>",
+        &[],
+    )
+    .unwrap();
+    assert!(peers.is_empty());
+}
+
+/// `gluster pool list --xml` output listing each (uuid, hostname) pair as a
+/// connected member of the cluster
+#[cfg(test)]
+pub(crate) fn pool_list_xml(peers: &[(&str, &str)]) -> String {
+    let mut xml =
+        "<cliOutput><opRet>0</opRet><opErrno>0</opErrno><opErrstr/><peerStatus>".to_string();
+    for &(uuid, hostname) in peers {
+        xml.push_str(&format!(
+            "<peer><uuid>{}</uuid><hostname>{}</hostname><connected>1</connected>\
+             <state>3</state><stateStr>Peer in Cluster</stateStr></peer>",
+            uuid, hostname
+        ));
+    }
+    xml.push_str("</peerStatus></cliOutput>");
+    xml
 }
 
 /// Runs gluster peer status and returns a Vec<Peer> representing all the peers
@@ -220,19 +423,7 @@ State:\s+(?P<state_detail>[a-zA-z ]+)\s+\((?P<state>\w+)\)"#;
 /// # Failures
 /// Returns GlusterError if the command failed to run
 pub fn peer_status() -> Result<Vec<Peer>, GlusterError> {
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("peer".to_string());
-    arg_list.push("status".to_string());
-
-    let output = run_command("gluster", &arg_list, true, false);
-    let output_str = String::from_utf8(output.stdout)?;
-    // Number of Peers: 1
-    // Hostname: 10.0.3.207
-    // Uuid: afbd338e-881b-4557-8764-52e259885ca3
-    // State: Peer in Cluster (Connected)
-    //
-
-    parse_peer_status(&output_str)
+    GlusterClient::default().peer_status()
 }
 
 // List all peers including localhost
@@ -243,79 +434,339 @@ pub fn peer_status() -> Result<Vec<Peer>, GlusterError> {
 /// # Failures
 /// Returns GlusterError if the command failed to run
 pub fn peer_list() -> Result<Vec<Peer>, GlusterError> {
-    let mut peers: Vec<Peer> = Vec::new();
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("pool".to_string());
-    arg_list.push("list".to_string());
+    GlusterClient::default().peer_list()
+}
 
-    let output = run_command("gluster", &arg_list, true, false);
-    let output_str = String::from_utf8(output.stdout)?;
+// Probe a peer and prevent double probing
+/// Adds a new peer to the cluster by hostname or ip address
+/// # Failures
+/// Returns GlusterError if the command failed to run
+pub fn peer_probe(hostname: &str) -> Result<i32, GlusterError> {
+    GlusterClient::default().peer_probe(hostname)
+}
 
-    for line in output_str.lines() {
-        if line.contains("State") {
-            continue;
-        } else {
-            let v: Vec<&str> = line.split('\t').collect();
-            let uuid = Uuid::parse_str(v[0])?;
-            let mut hostname = v[1].trim().to_string();
+/// Removes a peer from the cluster by hostname or ip address
+/// # Failures
+/// Returns GlusterError if the command failed to run
+pub fn peer_remove(hostname: &str, force: bool) -> Result<i32, GlusterError> {
+    GlusterClient::default().peer_remove(hostname, force)
+}
+
+/// Adds a new peer to the cluster and waits for it to be connected and in
+/// the cluster
+/// # Failures
+/// Returns GlusterError::PeerWaitTimeout if the peer doesn't get there
+/// within timeout or GlusterError if the probe fails
+pub fn peer_probe_and_wait(hostname: &str, timeout: Duration) -> Result<Peer, GlusterError> {
+    GlusterClient::default().peer_probe_and_wait(hostname, timeout)
+}
+
+/// Removes a peer from the cluster and waits for it to drop out of the pool
+/// # Failures
+/// Returns GlusterError::PeerWaitTimeout if the peer is still listed after
+/// timeout or GlusterError if the detach fails
+pub fn peer_remove_and_wait(
+    hostname: &str,
+    force: bool,
+    timeout: Duration,
+) -> Result<(), GlusterError> {
+    GlusterClient::default().peer_remove_and_wait(hostname, force, timeout)
+}
+
+// How often the pool is listed while waiting on a peer
+const PEER_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
-            // Translate back into an IP address if needed
-            let check_for_ip = hostname.parse::<IpAddr>();
+// Sleep until it is time to list the pool again.  Returns false instead once
+// the deadline has passed.
+fn wait_for_next_poll(deadline: Instant) -> bool {
+    let now = Instant::now();
+    if now >= deadline {
+        return false;
+    }
+    thread::sleep(PEER_POLL_INTERVAL.min(deadline - now));
+    true
+}
+
+impl GlusterClient {
+    /// This will query the Gluster peer list and return a Peer struct for the peer.
+    /// A peer known by any of its names or by another name or address of the
+    /// same server is found too.
+    /// # Failures
+    /// Returns GlusterError if the peer could not be found
+    pub fn get_peer(&self, hostname: &str) -> Result<Peer, GlusterError> {
+        let peer_list = self.peer_list()?;
+        let host = Host::new(hostname);
 
-            if check_for_ip.is_err() {
-                // It's a hostname so lets resolve it
-                hostname = match resolve_to_ip(&hostname) {
-                    Ok(ip_addr) => ip_addr,
+        if let Some(peer) = peer_list.iter().find(|peer| peer.is_known_as(&host)) {
+            debug!("Found peer: {:?}", peer);
+            return Ok(peer.clone());
+        }
+        // The pool may name the server differently.  A name that doesn't
+        // resolve, eg: of a peer that was just detached, is not a peer.
+        let resolver = self.resolver();
+        let addresses = match host.addresses(resolver) {
+            Ok(addresses) => addresses,
+            Err(e) => {
+                debug!("Can't resolve {}: {}", hostname, e);
+                return Err(GlusterError::PeerNotFound(hostname.to_string()));
+            }
+        };
+        let mut local_ips: Option<Vec<IpAddr>> = None;
+        for peer in peer_list {
+            // The pool lists this server as localhost
+            let found = if peer.hostname == "localhost" {
+                if local_ips.is_none() {
+                    match self.get_local_ips() {
+                        Ok(ips) => local_ips = Some(ips),
+                        Err(e) => {
+                            debug!("Skipping peer localhost: {}", e);
+                            continue;
+                        }
+                    }
+                }
+                let local_ips = local_ips.as_ref().map_or(&[][..], |l| &l[..]);
+                addresses.iter().any(|a| local_ips.contains(a))
+            } else {
+                match peer.hostname.addresses(resolver) {
+                    Ok(peer_addresses) => peer_addresses.iter().any(|a| addresses.contains(a)),
                     Err(e) => {
-                        return Err(GlusterError::new(e.to_string()));
+                        debug!("Skipping peer {}: {}", peer.hostname, e);
+                        false
                     }
-                };
+                }
+            };
+            if found {
+                debug!("Found peer: {:?}", peer);
+                return Ok(peer);
             }
-            debug!("hostname from peer list command is {:?}", &hostname);
+        }
+        Err(GlusterError::PeerNotFound(hostname.to_string()))
+    }
+
+    /// Runs gluster peer status and returns a Vec<Peer> representing all the peers
+    /// in the cluster
+    /// # Failures
+    /// Returns GlusterError if the command failed to run
+    pub fn peer_status(&self) -> Result<Vec<Peer>, GlusterError> {
+        let arg_list = ["peer", "status", "--xml"];
+        let output = self.gluster_checked(&arg_list)?;
+        let output_str = String::from_utf8(output.stdout)?;
+        parse_peer_status_xml(&output_str, &self.gluster_argv(&arg_list))
+    }
 
-            peers.push(Peer {
-                uuid,
-                hostname,
-                status: State::new(v[2]),
-            });
+    // List all peers including localhost
+    /// Runs gluster pool list and returns a Vec<Peer> representing all the peers
+    /// in the cluster
+    /// This also returns information for the localhost as a Peer.  peer_status()
+    /// does not
+    /// # Failures
+    /// Returns GlusterError if the command failed to run
+    pub fn peer_list(&self) -> Result<Vec<Peer>, GlusterError> {
+        let arg_list = ["pool", "list", "--xml"];
+        let output = self.gluster_checked(&arg_list)?;
+        let output_str = String::from_utf8(output.stdout)?;
+        let peers = parse_peer_status_xml(&output_str, &self.gluster_argv(&arg_list))?;
+        debug!("peers from pool list command are {:?}", &peers);
+        Ok(peers)
+    }
+
+    // Probe a peer and prevent double probing
+    /// Adds a new peer to the cluster by hostname or ip address
+    /// # Failures
+    /// Returns GlusterError if the command failed to run
+    pub fn peer_probe(&self, hostname: &str) -> Result<i32, GlusterError> {
+        let current_peers = self.peer_list()?;
+        let host = Host::new(hostname);
+        for peer in current_peers {
+            if peer.is_known_as(&host) {
+                // Bail instead of double probing
+                // return Err(format!("hostname: {} is already part of the cluster", hostname));
+                return Ok(0); //Does it make sense to say this is ok?
+            }
         }
+        let mut arg_list: Vec<String> = Vec::new();
+        arg_list.push("peer".to_string());
+        arg_list.push("probe".to_string());
+        arg_list.push(hostname.to_string());
+
+        self.gluster_mutate(&arg_list)?;
+
+        Ok(0)
     }
-    Ok(peers)
-}
 
-// Probe a peer and prevent double probing
-/// Adds a new peer to the cluster by hostname or ip address
-/// # Failures
-/// Returns GlusterError if the command failed to run
-pub fn peer_probe(hostname: &str) -> Result<i32, GlusterError> {
-    let current_peers = peer_list()?;
-    for peer in current_peers {
-        if peer.hostname == *hostname {
-            // Bail instead of double probing
-            // return Err(format!("hostname: {} is already part of the cluster", hostname));
-            return Ok(0); //Does it make sense to say this is ok?
+    /// Removes a peer from the cluster by hostname or ip address
+    /// # Failures
+    /// Returns GlusterError if the command failed to run
+    pub fn peer_remove(&self, hostname: &str, force: bool) -> Result<i32, GlusterError> {
+        let mut arg_list: Vec<String> = Vec::new();
+        arg_list.push("peer".to_string());
+        arg_list.push("detach".to_string());
+        arg_list.push(hostname.to_string());
+
+        if force {
+            arg_list.push("force".to_string());
         }
+
+        self.gluster_mutate(&arg_list)?;
+
+        Ok(0)
     }
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("peer".to_string());
-    arg_list.push("probe".to_string());
-    arg_list.push(hostname.to_string());
 
-    process_output(run_command("gluster", &arg_list, true, false))
+    /// Adds a new peer to the cluster and waits for it to be connected and in
+    /// the cluster.  A freshly probed peer spends a while in states like
+    /// "Accepted peer request" while it syncs with the cluster and volume
+    /// commands involving it fail until it is done.
+    /// # Failures
+    /// Returns GlusterError::PeerWaitTimeout if the peer doesn't get there
+    /// within timeout or GlusterError if the probe fails
+    pub fn peer_probe_and_wait(
+        &self,
+        hostname: &str,
+        timeout: Duration,
+    ) -> Result<Peer, GlusterError> {
+        self.peer_probe(hostname)?;
+        let deadline = Instant::now() + timeout;
+        let mut last_seen: Option<Box<Peer>> = None;
+        loop {
+            match self.get_peer(hostname) {
+                Ok(peer) => {
+                    if peer.is_healthy() {
+                        return Ok(peer);
+                    }
+                    debug!("Waiting on peer {:?}", peer);
+                    last_seen = Some(Box::new(peer));
+                }
+                // The probe may not have been recorded yet
+                Err(GlusterError::PeerNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+            if !wait_for_next_poll(deadline) {
+                return Err(GlusterError::PeerWaitTimeout {
+                    hostname: hostname.to_string(),
+                    last_seen,
+                    timeout,
+                });
+            }
+        }
+    }
+
+    /// Removes a peer from the cluster and waits for it to drop out of the
+    /// pool
+    /// # Failures
+    /// Returns GlusterError::PeerWaitTimeout if the peer is still listed
+    /// after timeout or GlusterError if the detach fails
+    pub fn peer_remove_and_wait(
+        &self,
+        hostname: &str,
+        force: bool,
+        timeout: Duration,
+    ) -> Result<(), GlusterError> {
+        self.peer_remove(hostname, force)?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let last_seen = match self.get_peer(hostname) {
+                Ok(peer) => peer,
+                Err(GlusterError::PeerNotFound(_)) => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            if !wait_for_next_poll(deadline) {
+                return Err(GlusterError::PeerWaitTimeout {
+                    hostname: hostname.to_string(),
+                    last_seen: Some(Box::new(last_seen)),
+                    timeout,
+                });
+            }
+        }
+    }
 }
 
-/// Removes a peer from the cluster by hostname or ip address
-/// # Failures
-/// Returns GlusterError if the command failed to run
-pub fn peer_remove(hostname: &str, force: bool) -> Result<i32, GlusterError> {
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("peer".to_string());
-    arg_list.push("detach".to_string());
-    arg_list.push(hostname.to_string());
+#[test]
+fn test_peer_probe_and_wait() {
+    use executor::CannedExecutor;
+    use std::sync::Arc;
+
+    let pool_list = "<cliOutput><opRet>0</opRet><opErrno>0</opErrno><opErrstr/><peerStatus>\
+                     <peer><uuid>afbd338e-881b-4557-8764-52e259885ca3</uuid>\
+                     <hostname>10.0.0.5</hostname><connected>1</connected>\
+                     <state>3</state><stateStr>Peer in Cluster</stateStr></peer>\
+                     <peer><uuid>fa3b031a-c4ef-43c5-892d-4b909bc5cd5d</uuid>\
+                     <hostname>10.0.0.6</hostname><connected>1</connected>\
+                     <state>4</state><stateStr>Accepted peer request</stateStr></peer>\
+                     </peerStatus></cliOutput>";
+    let canned = Arc::new(CannedExecutor::new().respond(&["pool", "list"], 0, pool_list, ""));
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+
+    // Already in the cluster so there is nothing to probe or wait for
+    let peer = client
+        .peer_probe_and_wait("10.0.0.5", Duration::from_secs(10))
+        .unwrap();
+    assert_eq!(peer.hostname, "10.0.0.5");
+    assert_eq!(canned.calls().len(), 2);
 
-    if force {
-        arg_list.push("force".to_string());
+    let start = Instant::now();
+    match client.peer_probe_and_wait("10.0.0.6", Duration::from_millis(20)) {
+        Err(GlusterError::PeerWaitTimeout {
+            ref hostname,
+            last_seen: Some(ref peer),
+            ..
+        }) => {
+            assert_eq!(hostname, "10.0.0.6");
+            assert_eq!(peer.status, State::AcceptedPeerRequest);
+        }
+        other => panic!("expected PeerWaitTimeout, got {:?}", other),
     }
+    assert!(start.elapsed() < PEER_POLL_INTERVAL);
 
-    process_output(run_command("gluster", &arg_list, true, false))
+    // Detaching waits for the peer to leave the pool
+    client
+        .peer_remove_and_wait("10.0.0.7", false, Duration::from_secs(10))
+        .unwrap();
+    let detach: Vec<String> = vec!["gluster", "--mode=script", "peer", "detach", "10.0.0.7"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    assert!(canned.calls().contains(&detach));
+    match client.peer_remove_and_wait("10.0.0.5", false, Duration::from_millis(1)) {
+        Err(GlusterError::PeerWaitTimeout {
+            last_seen: Some(ref peer),
+            ..
+        }) => assert_eq!(peer.status, State::PeerInCluster),
+        other => panic!("expected PeerWaitTimeout, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_peer_by_address() {
+    use executor::CannedExecutor;
+    use host::CannedResolver;
+    use std::sync::Arc;
+
+    let pool_list = pool_list_xml(&[
+        ("afbd338e-881b-4557-8764-52e259885ca3", "gone.example.com"),
+        ("fa3b031a-c4ef-43c5-892d-4b909bc5cd5d", "gluster-1"),
+    ]);
+    let canned = Arc::new(CannedExecutor::new().respond(&["pool", "list"], 0, &pool_list, ""));
+    let resolver = Arc::new(
+        CannedResolver::new()
+            .host("gluster-1", &["10.0.0.1"])
+            .host("gluster-1.example.com", &["10.0.0.1"]),
+    );
+    let client = GlusterClient::builder()
+        .executor(canned)
+        .resolver(resolver)
+        .build();
+
+    // gone.example.com doesn't resolve and is skipped
+    let peer = client.get_peer("gluster-1.example.com").unwrap();
+    assert_eq!(peer.hostname, "gluster-1");
+    match client.get_peer("nowhere.example.com") {
+        Err(GlusterError::PeerNotFound(ref hostname)) => {
+            assert_eq!(hostname, "nowhere.example.com")
+        }
+        other => panic!("expected PeerNotFound, got {:?}", other),
+    }
+    // A detached peer whose name no longer resolves is gone
+    client
+        .peer_remove_and_wait("detached.example.com", false, Duration::from_secs(10))
+        .unwrap();
 }
@@ -0,0 +1,143 @@
+//! Retrying commands that fail because of transient cluster conditions.
+//!
+//! glusterd only runs one transaction at a time across the whole cluster.
+//! When two nodes issue commands at once one of them fails with "Another
+//! transaction is in progress" or "Locking failed on ...".  A RetryPolicy on
+//! the GlusterClient runs such commands again after an exponential backoff.
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use super::{CliFailure, GlusterError};
+
+/// The kinds of failure a RetryPolicy can retry
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RetryOn {
+    /// "Another transaction is in progress"
+    TransactionInProgress,
+    /// "Locking failed on <host>"
+    LockingFailed,
+    /// Server or client quorum is not met
+    QuorumNotMet,
+    /// A peer the command needed is not connected
+    PeerNotConnected,
+    /// The command ran out of time.  Only use this for commands that are safe
+    /// to run twice.
+    Timeout,
+}
+
+impl RetryOn {
+    fn matches(&self, err: &GlusterError) -> bool {
+        let kind = match *err {
+            GlusterError::Timeout { .. } => return *self == RetryOn::Timeout,
+            GlusterError::CommandFailed { ref kind, .. } => kind,
+            _ => return false,
+        };
+        let retry_on = match *kind {
+            CliFailure::TransactionInProgress => RetryOn::TransactionInProgress,
+            CliFailure::LockingFailed { .. } => RetryOn::LockingFailed,
+            CliFailure::QuorumNotMet => RetryOn::QuorumNotMet,
+            CliFailure::PeerNotConnected { .. } => RetryOn::PeerNotConnected,
+            _ => return false,
+        };
+        *self == retry_on
+    }
+}
+
+/// How often and how long to wait before running a failed command again.
+/// The wait doubles after every attempt, up to max_backoff, and is randomized
+/// so that nodes which collided once don't collide again.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    retry_on: Vec<RetryOn>,
+}
+
+impl RetryPolicy {
+    /// 5 attempts starting with a 500ms backoff capped at 10s, retrying
+    /// transaction lock contention only
+    pub fn new() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            retry_on: vec![RetryOn::TransactionInProgress, RetryOn::LockingFailed],
+        }
+    }
+
+    /// Run the command at most this many times in total
+    pub fn max_attempts(mut self, max_attempts: u32) -> RetryPolicy {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Wait about this long before the first retry
+    pub fn initial_backoff(mut self, backoff: Duration) -> RetryPolicy {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Never wait longer than this between attempts
+    pub fn max_backoff(mut self, backoff: Duration) -> RetryPolicy {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Retry these kinds of failure instead of the defaults
+    pub fn retry_on(mut self, retry_on: Vec<RetryOn>) -> RetryPolicy {
+        self.retry_on = retry_on;
+        self
+    }
+
+    /// The most times a command is run
+    pub fn attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether err is worth retrying under this policy
+    pub fn is_retryable(&self, err: &GlusterError) -> bool {
+        self.retry_on.iter().any(|r| r.matches(err))
+    }
+
+    /// How long to wait before retry number `retry`, counting from 1.  This
+    /// is somewhere between half and all of the exponential backoff.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |b| b.min(self.max_backoff));
+        let half = backoff / 2;
+        let spread = half.as_nanos() as u64 + 1;
+        half + Duration::from_nanos(random() % spread)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy::new()
+    }
+}
+
+// Every RandomState is seeded with fresh random keys so hashing nothing with
+// one gives a random number without pulling in a crate for it
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[test]
+fn test_retry_policy_delay() {
+    let policy = RetryPolicy::new()
+        .initial_backoff(Duration::from_millis(100))
+        .max_backoff(Duration::from_millis(1000));
+    for _ in 0..20 {
+        let first = policy.delay(1);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+        let third = policy.delay(3);
+        assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+        let capped = policy.delay(30);
+        assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_millis(1000));
+    }
+}
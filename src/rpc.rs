@@ -932,7 +932,7 @@ pub fn unpack_replyheader<T: Read>(
 
     if msg_type != REPLY {
         // Invalid REPLY
-        return Err(super::GlusterError::new(format!(
+        return Err(super::GlusterError::XdrDecode(format!(
             "Invalid reply with msg_type: {}",
             msg_type
         )));
@@ -945,7 +945,7 @@ pub fn unpack_replyheader<T: Read>(
         if reason == RPC_MISMATCH {
             let low = data.read_u32::<BigEndian>()?;
             let high = data.read_u32::<BigEndian>()?;
-            return Err(super::GlusterError::new(format!(
+            return Err(super::GlusterError::RpcDenied(format!(
                 "MSG_DENIED: RPC_MISMATCH low: {} \
                  high: {}",
                 low, high
@@ -953,12 +953,15 @@ pub fn unpack_replyheader<T: Read>(
         }
         if reason == AUTH_ERROR {
             let err = data.read_u32::<BigEndian>()?;
-            return Err(super::GlusterError::new(format!(
+            return Err(super::GlusterError::RpcDenied(format!(
                 "MSG_DENIED: AUTH_ERROR {}",
                 err
             )));
         }
-        return Err(super::GlusterError::new(format!("MSG_DENIED: {}", reason)));
+        return Err(super::GlusterError::RpcDenied(format!(
+            "MSG_DENIED: {}",
+            reason
+        )));
     }
     if stat == MSG_ACCEPTED {
         let auth_flavor = data.read_i32::<BigEndian>()?;
@@ -973,32 +976,32 @@ pub fn unpack_replyheader<T: Read>(
             stuff: stuff.into_bytes(),
         };
         match accept_message {
-            PROG_UNAVAIL => Err(super::GlusterError::new(
+            PROG_UNAVAIL => Err(super::GlusterError::RpcFailed(
                 "call failed PROG_UNAVAIL".to_string(),
             )),
             PROG_MISMATCH => {
                 let low = data.read_u32::<BigEndian>()?;
                 let high = data.read_u32::<BigEndian>()?;
-                Err(super::GlusterError::new(format!(
+                Err(super::GlusterError::RpcFailed(format!(
                     "Call failed: PROG_MISMATCH low: \
                      {} high: {}",
                     low, high
                 )))
             }
-            PROC_UNAVAIL => Err(super::GlusterError::new(
+            PROC_UNAVAIL => Err(super::GlusterError::RpcFailed(
                 "call failed PROC_UNAVAIL".to_string(),
             )),
-            GARBAGE_ARGS => Err(super::GlusterError::new(
+            GARBAGE_ARGS => Err(super::GlusterError::RpcFailed(
                 "call failed GARBAGE_ARGS".to_string(),
             )),
             SUCCESS => Ok((xid, rpc_auth)),
-            _ => Err(super::GlusterError::new(format!(
+            _ => Err(super::GlusterError::RpcFailed(format!(
                 "Call failed: {}",
                 accept_message
             ))),
         }
     } else {
-        Err(super::GlusterError::new(format!(
+        Err(super::GlusterError::XdrDecode(format!(
             "MSG neither denied or accepted: {}",
             stat
         )))
@@ -1074,7 +1077,7 @@ pub fn recv_fragment<T: Read>(socket: &mut T) -> Result<(bool, Vec<u8>), super::
     socket.by_ref().take(4).read_to_end(&mut buffer)?;
 
     if buffer.len() < 4 {
-        return Err(super::GlusterError::new("EOF Error".to_string()));
+        return Err(super::GlusterError::XdrDecode("EOF Error".to_string()));
     }
     let mut buf = Cursor::new(&buffer[..]);
     let header = buf.read_u32::<BigEndian>()?;
@@ -0,0 +1,572 @@
+//! Reads volume and peer information straight out of glusterd's working
+//! directory, /var/lib/glusterd.
+//!
+//! Advantages: Works when glusterd is down or wedged and never blocks on the
+//! cluster lock
+//! Disadvantages: Needs to be run on a gluster server and only knows what
+//! this node has last written to disk.  Nothing here knows whether a peer is
+//! currently connected.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[cfg(test)]
+use super::host::CannedResolver;
+use super::host::{Resolver, SystemResolver};
+use super::local;
+use super::peer::{Peer, State};
+use super::volume::{Brick, Geometry, Transport, Volume, VolumeType};
+use super::{GlusterError, Host};
+use uuid::Uuid;
+
+/// Where glusterd keeps its state by default
+pub const DEFAULT_GLUSTERD_DIR: &str = "/var/lib/glusterd";
+
+/// Progress of a rebalance or remove-brick data migration as recorded in
+/// node_state.info
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RebalanceStatus {
+    NotStarted,
+    Started,
+    Stopped,
+    Complete,
+    Failed,
+    LayoutFixStarted,
+    LayoutFixStopped,
+    LayoutFixComplete,
+    LayoutFixFailed,
+    Unknown(u32),
+}
+
+impl RebalanceStatus {
+    /// Create a RebalanceStatus from glusterd's gf_defrag_status_t value
+    pub fn from_code(code: u32) -> RebalanceStatus {
+        match code {
+            0 => RebalanceStatus::NotStarted,
+            1 => RebalanceStatus::Started,
+            2 => RebalanceStatus::Stopped,
+            3 => RebalanceStatus::Complete,
+            4 => RebalanceStatus::Failed,
+            5 => RebalanceStatus::LayoutFixStarted,
+            6 => RebalanceStatus::LayoutFixStopped,
+            7 => RebalanceStatus::LayoutFixComplete,
+            8 => RebalanceStatus::LayoutFixFailed,
+            _ => RebalanceStatus::Unknown(code),
+        }
+    }
+}
+
+/// The operation that last migrated data on a volume
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RebalanceOp {
+    None,
+    Rebalance,
+    RemoveBrick,
+    Unknown(u32),
+}
+
+impl RebalanceOp {
+    /// Create a RebalanceOp from glusterd's glusterd_op_t value
+    pub fn from_code(code: u32) -> RebalanceOp {
+        match code {
+            0 => RebalanceOp::None,
+            9 => RebalanceOp::RemoveBrick,
+            19 => RebalanceOp::Rebalance,
+            _ => RebalanceOp::Unknown(code),
+        }
+    }
+}
+
+/// The rebalance state this node has recorded for a volume
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodeState {
+    pub rebalance_status: RebalanceStatus,
+    pub rebalance_op: RebalanceOp,
+    /// The task id of the last rebalance or remove-brick.  Nil if none ran.
+    pub rebalance_id: Uuid,
+}
+
+/// A glusterd working directory
+#[derive(Clone)]
+pub struct GlusterdStore {
+    dir: PathBuf,
+    resolver: Arc<dyn Resolver>,
+    local_addresses: Option<Vec<IpAddr>>,
+}
+
+impl fmt::Debug for GlusterdStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GlusterdStore")
+            .field("dir", &self.dir)
+            .field("local_addresses", &self.local_addresses)
+            .finish()
+    }
+}
+
+impl GlusterdStore {
+    /// Read the glusterd working directory at `dir`
+    pub fn new<P: Into<PathBuf>>(dir: P) -> GlusterdStore {
+        GlusterdStore {
+            dir: dir.into(),
+            resolver: Arc::new(SystemResolver),
+            local_addresses: None,
+        }
+    }
+
+    /// Look up brick and peer hostnames with `resolver` instead of the
+    /// system's resolver
+    pub fn resolver(mut self, resolver: Arc<dyn Resolver>) -> GlusterdStore {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Treat `addresses` as this node's addresses instead of listing its
+    /// interfaces.  Useful when reading a copy of another node's directory.
+    pub fn local_addresses(mut self, addresses: Vec<IpAddr>) -> GlusterdStore {
+        self.local_addresses = Some(addresses);
+        self
+    }
+
+    /// Lists the names of all volumes this node knows about
+    /// # Failures
+    /// Returns GlusterError if the vols directory can't be read
+    pub fn volume_list(&self) -> Result<Vec<String>, GlusterError> {
+        let mut volumes: Vec<String> = Vec::new();
+        for entry in fs::read_dir(self.dir.join("vols"))? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                volumes.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        volumes.sort();
+        Ok(volumes)
+    }
+
+    /// The UUID of this node
+    /// # Failures
+    /// Returns GlusterError if glusterd.info can't be read or has no UUID
+    pub fn local_uuid(&self) -> Result<Uuid, GlusterError> {
+        let path = self.dir.join("glusterd.info");
+        let info = read_key_values(&path)?;
+        match info.get("UUID") {
+            Some(uuid) => Ok(Uuid::parse_str(uuid)?),
+            None => Err(GlusterError::parse_failure(
+                path.to_string_lossy(),
+                "glusterd.info UUID",
+            )),
+        }
+    }
+
+    /// Every peer this node has been probed with, not including itself.
+    /// The hostname is the first name the peer was known by.  Whether a peer
+    /// is connected isn't stored so connected is always false.
+    /// # Failures
+    /// Returns GlusterError if a peer file can't be read or parsed
+    pub fn peers(&self) -> Result<Vec<Peer>, GlusterError> {
+        let mut peers: Vec<Peer> = Vec::new();
+        let entries = match fs::read_dir(self.dir.join("peers")) {
+            Ok(entries) => entries,
+            // A node that was never probed has no peers directory
+            Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(peers),
+            Err(e) => return Err(GlusterError::from(e)),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let info = read_key_values(&path)?;
+            let uuid = match info.get("uuid") {
+                Some(uuid) => Uuid::parse_str(uuid)?,
+                None => {
+                    return Err(GlusterError::parse_failure(
+                        path.to_string_lossy(),
+                        "peer uuid",
+                    ))
+                }
+            };
+            let state = match info.get("state") {
+                Some(state) => u32::from_str(state)?,
+                None => 0,
+            };
+            let mut hostnames: Vec<Host> = Vec::new();
+            let mut i = 1;
+            while let Some(name) = info.get(&format!("hostname{}", i)) {
+                hostnames.push(Host::new(name.clone()));
+                i += 1;
+            }
+            peers.push(Peer {
+                uuid,
+                hostname: hostnames.first().cloned().unwrap_or_else(|| Host::new("")),
+                hostnames,
+                connected: false,
+                state_code: Some(state),
+                status: State::from_code(state),
+                state_text: String::new(),
+            });
+        }
+        peers.sort();
+        Ok(peers)
+    }
+
+    /// Returns a Volume built entirely from the files glusterd keeps for it.
+    /// Bricks are matched to peers by name, then by address.  A brick on no
+    /// known host gets a nil UUID and State::Unknown.
+    /// # Failures
+    /// Returns GlusterError::VolumeNotFound if there is no such volume or
+    /// GlusterError if its files can't be read or parsed
+    pub fn volume_info(&self, volume: &str) -> Result<Volume, GlusterError> {
+        let vol_dir = self.dir.join("vols").join(volume);
+        let info_path = vol_dir.join("info");
+        let info = match read_key_values(&info_path) {
+            Ok(info) => info,
+            Err(GlusterError::IoError(ref e)) if e.kind() == ErrorKind::NotFound => {
+                return Err(GlusterError::VolumeNotFound(volume.to_string()));
+            }
+            Err(e) => return Err(e),
+        };
+        let count = |key: &str| -> Result<u32, GlusterError> {
+            match info.get(key) {
+                Some(value) => Ok(u32::from_str(value)?),
+                None => Ok(0),
+            }
+        };
+
+        let geometry = Geometry {
+            replica_count: count("replica_count")?.max(1),
+            arbiter_count: count("arbiter_count")?,
+            disperse_count: count("disperse_count")?,
+            redundancy_count: count("redundancy_count")?,
+            stripe_count: count("stripe_count")?.max(1),
+        };
+        let brick_count = count("count")?;
+        let vol_type = volume_type(count("type")?, brick_count, &geometry);
+        let status = match count("status")? {
+            0 => "Created",
+            1 => "Started",
+            2 => "Stopped",
+            _ => "Unknown",
+        };
+        let id = match info.get("volume-id") {
+            Some(id) => Uuid::parse_str(id)?,
+            None => Uuid::nil(),
+        };
+        let transport = match info.get("transport-type") {
+            Some(transport) => Transport::from_str(transport)?,
+            None => Transport::Tcp,
+        };
+        // Every volume option has a dot in its name.  Nothing else does.
+        let options: BTreeMap<String, String> = info
+            .iter()
+            .filter(|&(key, _)| key.contains('.'))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        let local_uuid = self.local_uuid()?;
+        let peers = self.peers()?;
+        let mut local_addresses: Option<Vec<IpAddr>> = None;
+        let mut bricks: Vec<Brick> = Vec::new();
+        for i in 0..brick_count {
+            let brick_file = match info.get(&format!("brick-{}", i)) {
+                Some(name) => name,
+                None => {
+                    return Err(GlusterError::parse_failure(
+                        info_path.to_string_lossy(),
+                        format!("brick-{}", i),
+                    ));
+                }
+            };
+            let brick_info = read_key_values(&vol_dir.join("bricks").join(brick_file))?;
+            let hostname = Host::new(brick_info.get("hostname").cloned().unwrap_or_default());
+            let path = brick_info.get("path").cloned().unwrap_or_default();
+
+            let peer = self.brick_peer(hostname, &peers, local_uuid, &mut local_addresses);
+            // The last brick of each replica set is the arbiter
+            let is_arbiter = geometry.arbiter_count > 0
+                && i % geometry.replica_count == geometry.replica_count - 1;
+            bricks.push(Brick {
+                peer,
+                path: PathBuf::from(path),
+                is_arbiter,
+            });
+        }
+
+        Ok(Volume {
+            name: volume.to_string(),
+            vol_type,
+            id,
+            status: status.to_string(),
+            transport,
+            bricks,
+            options,
+            replica_count: geometry.replica_count,
+            arbiter_count: geometry.arbiter_count,
+            disperse_count: geometry.disperse_count,
+            redundancy_count: geometry.redundancy_count,
+            dist_count: geometry.dist_count(),
+            stripe_count: geometry.stripe_count,
+            snapshot_count: self.snapshot_count(volume)?,
+        })
+    }
+
+    /// The rebalance state recorded in the volume's node_state.info
+    /// # Failures
+    /// Returns GlusterError::VolumeNotFound if there is no such volume or
+    /// GlusterError if node_state.info can't be read or parsed
+    pub fn node_state(&self, volume: &str) -> Result<NodeState, GlusterError> {
+        let vol_dir = self.dir.join("vols").join(volume);
+        if !vol_dir.is_dir() {
+            return Err(GlusterError::VolumeNotFound(volume.to_string()));
+        }
+        let state = read_key_values(&vol_dir.join("node_state.info"))?;
+        let code = |key: &str| -> Result<u32, GlusterError> {
+            match state.get(key) {
+                Some(value) => Ok(u32::from_str(value)?),
+                None => Ok(0),
+            }
+        };
+        Ok(NodeState {
+            rebalance_status: RebalanceStatus::from_code(code("rebalance_status")?),
+            rebalance_op: RebalanceOp::from_code(code("rebalance_op")?),
+            rebalance_id: match state.get("rebalance-id") {
+                Some(id) => Uuid::parse_str(id)?,
+                None => Uuid::nil(),
+            },
+        })
+    }
+
+    // Bricks name their host however the volume was created, so a brick is
+    // matched to a peer by name first and by address after that.  A brick
+    // that matches nothing is kept with a nil UUID and State::Unknown rather
+    // than assumed to be on this node.
+    fn brick_peer(
+        &self,
+        hostname: Host,
+        peers: &[Peer],
+        local_uuid: Uuid,
+        local_addresses: &mut Option<Vec<IpAddr>>,
+    ) -> Peer {
+        if let Some(peer) = peers.iter().find(|peer| peer.is_known_as(&hostname)) {
+            return Peer {
+                hostname,
+                ..peer.clone()
+            };
+        }
+        let addresses = match hostname.addresses(&*self.resolver) {
+            Ok(addresses) => addresses.to_vec(),
+            Err(e) => {
+                debug!("Unable to resolve brick host {}: {}", hostname, e);
+                Vec::new()
+            }
+        };
+        let local = local_addresses.get_or_insert_with(|| match self.local_addresses {
+            Some(ref addresses) => addresses.clone(),
+            None => local::local_addresses().unwrap_or_else(|e| {
+                debug!("Unable to list local addresses: {}", e);
+                Vec::new()
+            }),
+        });
+        if addresses.iter().any(|a| local.contains(a)) {
+            return Peer {
+                connected: true,
+                ..Peer::new(local_uuid, hostname, State::Local)
+            };
+        }
+        for peer in peers {
+            for name in &peer.hostnames {
+                match name.addresses(&*self.resolver) {
+                    Ok(peer_addresses) => {
+                        if peer_addresses.iter().any(|a| addresses.contains(a)) {
+                            return Peer {
+                                hostname,
+                                ..peer.clone()
+                            };
+                        }
+                    }
+                    Err(e) => debug!("Unable to resolve peer host {}: {}", name, e),
+                }
+            }
+        }
+        debug!(
+            "Brick host {} doesn't match this node or any peer",
+            hostname
+        );
+        Peer::new(Uuid::nil(), hostname, State::Unknown)
+    }
+
+    // Snapshot volumes record the volume they were taken from in their own
+    // info file under snaps/<snapshot>/<snapshot volume>/info
+    fn snapshot_count(&self, volume: &str) -> Result<u32, GlusterError> {
+        let snaps = match fs::read_dir(self.dir.join("snaps")) {
+            Ok(snaps) => snaps,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(GlusterError::from(e)),
+        };
+        let mut count = 0;
+        for snap in snaps {
+            let snap = snap?;
+            if !snap.file_type()?.is_dir() {
+                continue;
+            }
+            for snap_vol in fs::read_dir(snap.path())? {
+                let info_path = snap_vol?.path().join("info");
+                if !info_path.is_file() {
+                    continue;
+                }
+                let info = read_key_values(&info_path)?;
+                if info.get("parent_volname").map(|v| &v[..]) == Some(volume) {
+                    count += 1;
+                }
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl Default for GlusterdStore {
+    fn default() -> GlusterdStore {
+        GlusterdStore::new(DEFAULT_GLUSTERD_DIR)
+    }
+}
+
+/// Returns a Volume read from /var/lib/glusterd without talking to glusterd
+/// # Failures
+/// Returns GlusterError if the volume doesn't exist or its files can't be
+/// read or parsed
+pub fn volume_info(volume: &str) -> Result<Volume, GlusterError> {
+    GlusterdStore::default().volume_info(volume)
+}
+
+// glusterd stores everything as key=value lines
+fn read_key_values(path: &Path) -> Result<HashMap<String, String>, GlusterError> {
+    let f = BufReader::new(File::open(path)?);
+    let mut values: HashMap<String, String> = HashMap::new();
+    for line in f.lines() {
+        let line = line?;
+        let mut parts = line.splitn(2, '=');
+        if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    Ok(values)
+}
+
+// glusterd stores the cluster type and works out whether it is distributed
+// from the brick count
+fn volume_type(type_code: u32, brick_count: u32, geometry: &Geometry) -> VolumeType {
+    let distributed = brick_count > geometry.dist_count();
+    match (type_code, distributed) {
+        (1, false) => VolumeType::Stripe,
+        (1, true) => VolumeType::DistributedAndStripe,
+        (2, false) => VolumeType::Replicate,
+        (2, true) => VolumeType::DistributedAndReplicate,
+        (3, false) => VolumeType::StripedAndReplicate,
+        (3, true) => VolumeType::DistributedAndStripedAndReplicate,
+        (4, false) => VolumeType::Disperse,
+        (4, true) => VolumeType::DistributedAndDisperse,
+        _ => VolumeType::Distribute,
+    }
+}
+
+#[test]
+fn test_glusterd_store_volume_info() {
+    let store = GlusterdStore::new("tests/glusterd")
+        .resolver(Arc::new(CannedResolver::new()))
+        .local_addresses(vec!["10.0.2.81".parse().unwrap()]);
+    assert_eq!(
+        store.volume_list().unwrap(),
+        vec!["alias".to_string(), "test".to_string()]
+    );
+
+    let vol = store.volume_info("test").unwrap();
+    assert_eq!(vol.name, "test");
+    assert_eq!(vol.vol_type, VolumeType::DistributedAndReplicate);
+    assert_eq!(
+        vol.id,
+        Uuid::parse_str("e7d940ba-8b7c-4e37-a664-2975bc8452fc").unwrap()
+    );
+    assert_eq!(vol.status, "Started");
+    assert_eq!(vol.transport, Transport::Tcp);
+    assert_eq!(vol.replica_count, 3);
+    assert_eq!(vol.dist_count, 3);
+    assert_eq!(vol.snapshot_count, 1);
+    assert_eq!(vol.options.len(), 12);
+    assert_eq!(
+        vol.options.get("features.scrub"),
+        Some(&"Active".to_string())
+    );
+    assert!(!vol.options.contains_key("snap-max-hard-limit"));
+
+    assert_eq!(vol.bricks.len(), 9);
+    let local = Uuid::parse_str("9a3f3f3e-2b0f-4a10-9e8d-0f2d5f0c6a11").unwrap();
+    assert_eq!(vol.bricks[0].peer.uuid, local);
+    assert_eq!(vol.bricks[0].peer.status, State::Local);
+    assert_eq!(vol.bricks[0].path, PathBuf::from("/mnt/sdc/brick"));
+    assert_eq!(vol.bricks[1].peer.hostname, "10.0.2.82");
+    assert_eq!(vol.bricks[1].peer.status, State::PeerInCluster);
+    // Known by its second hostname
+    assert_eq!(
+        vol.bricks[2].peer.uuid,
+        Uuid::parse_str("afbd338e-881b-4557-8764-52e259885ca3").unwrap()
+    );
+    assert_eq!(vol.bricks[2].peer.status, State::PeerRejected);
+    assert_eq!(vol.bricks[8].path, PathBuf::from("/mnt/sde/brick"));
+
+    match store.volume_info("missing") {
+        Err(GlusterError::VolumeNotFound(ref volume)) => assert_eq!(volume, "missing"),
+        other => panic!("expected VolumeNotFound, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_glusterd_store_brick_aliases() {
+    // Bricks named by something other than the name in the peer file
+    let resolver = CannedResolver::new()
+        .host("gluster-1.example.com", &["10.0.2.81"])
+        .host("gluster-2", &["10.0.2.82"])
+        .host("gluster-2.example.com", &["10.0.2.82"]);
+    let store = GlusterdStore::new("tests/glusterd")
+        .resolver(Arc::new(resolver))
+        .local_addresses(vec!["10.0.2.81".parse().unwrap()]);
+    let vol = store.volume_info("alias").unwrap();
+    assert_eq!(vol.vol_type, VolumeType::Distribute);
+    assert_eq!(vol.bricks.len(), 3);
+
+    assert_eq!(vol.bricks[0].peer.hostname, "gluster-1.example.com");
+    assert_eq!(
+        vol.bricks[0].peer.uuid,
+        Uuid::parse_str("9a3f3f3e-2b0f-4a10-9e8d-0f2d5f0c6a11").unwrap()
+    );
+    assert_eq!(vol.bricks[0].peer.status, State::Local);
+
+    assert_eq!(vol.bricks[1].peer.hostname, "gluster-2");
+    assert_eq!(
+        vol.bricks[1].peer.uuid,
+        Uuid::parse_str("5f45e89a-23c1-41dd-b0cd-fd9cf37f1520").unwrap()
+    );
+    assert_eq!(vol.bricks[1].peer.status, State::PeerInCluster);
+
+    // Doesn't resolve and isn't a peer, so it isn't assumed to be local
+    assert_eq!(vol.bricks[2].peer.hostname, "gluster-4.example.com");
+    assert_eq!(vol.bricks[2].peer.uuid, Uuid::nil());
+    assert_eq!(vol.bricks[2].peer.status, State::Unknown);
+    assert!(!vol.bricks[2].peer.connected);
+}
+
+#[test]
+fn test_glusterd_store_node_state() {
+    let store = GlusterdStore::new("tests/glusterd");
+    assert_eq!(
+        store.node_state("test").unwrap(),
+        NodeState {
+            rebalance_status: RebalanceStatus::Complete,
+            rebalance_op: RebalanceOp::Rebalance,
+            rebalance_id: Uuid::parse_str("1e4b4bd6-5ae4-4e23-8b7b-73f1e4e0a7b6").unwrap(),
+        }
+    );
+    let peers = store.peers().unwrap();
+    assert_eq!(peers.len(), 2);
+    assert_eq!(peers[1].hostname, "gluster-3.example.com");
+}
@@ -0,0 +1,290 @@
+//! Which Gluster release is being talked to and what it can do.
+//!
+//! Commands and output formats change between releases.  Striped and tiered
+//! volumes were removed in 6.0 and `gluster get-state` only exists since
+//! 3.10.  Operations that depend on the release check it against the table
+//! of Features here and fail with GlusterError::Unsupported instead of
+//! handing glusterd a command it doesn't understand.
+use std::fmt;
+use std::str::FromStr;
+
+use super::{GlusterClient, GlusterError};
+
+/// A Gluster release, eg: 3.12.15 or 10.1
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct GlusterVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl GlusterVersion {
+    pub fn new(major: u32, minor: u32, patch: u32) -> GlusterVersion {
+        GlusterVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// The cluster op-version this release introduced.  A cluster can only
+    /// use what a release added once cluster.op-version has been raised to
+    /// at least this.
+    pub fn op_version(&self) -> u32 {
+        self.major * 10000 + self.minor * 100 + self.patch
+    }
+
+    /// Whether this release can do `feature`
+    pub fn supports(&self, feature: Feature) -> bool {
+        let &(_, added, removed) = FEATURES
+            .iter()
+            .find(|f| f.0 == feature)
+            .expect("every feature is in the table");
+        let not_removed = match removed {
+            Some(removed) => *self < removed,
+            None => true,
+        };
+        *self >= added && not_removed
+    }
+}
+
+impl fmt::Display for GlusterVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for GlusterVersion {
+    type Err = GlusterError;
+
+    /// Parses "10.1", "3.12.15" and packaged versions like "9.4-1ubuntu1".
+    /// Missing parts are 0.
+    fn from_str(s: &str) -> Result<GlusterVersion, GlusterError> {
+        let mut parts: Vec<u32> = Vec::new();
+        for part in s.trim().split('.').take(3) {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            if digits.is_empty() {
+                break;
+            }
+            parts.push(u32::from_str(&digits)?);
+            // Anything after the digits is packaging, not more version
+            if digits.len() != part.len() {
+                break;
+            }
+        }
+        if parts.is_empty() {
+            return Err(GlusterError::parse_failure(s, "gluster version"));
+        }
+        parts.resize(3, 0);
+        Ok(GlusterVersion::new(parts[0], parts[1], parts[2]))
+    }
+}
+
+/// Functionality that only some Gluster releases have
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Feature {
+    /// Replica volumes with arbiter bricks
+    Arbiter,
+    /// `gluster get-state`
+    GetState,
+    /// The cluster.max-op-version volume option
+    MaxOpVersion,
+    /// Striped volumes
+    Stripe,
+    /// Tiered volumes with a hot tier
+    Tier,
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Feature::Arbiter => "arbiter volumes",
+            Feature::GetState => "get-state",
+            Feature::MaxOpVersion => "cluster.max-op-version",
+            Feature::Stripe => "striped volumes",
+            Feature::Tier => "tiered volumes",
+        })
+    }
+}
+
+// Each feature with the first release that had it and the first release that
+// didn't anymore
+const FEATURES: &[(Feature, GlusterVersion, Option<GlusterVersion>)] = &[
+    (Feature::Arbiter, v(3, 7, 0), None),
+    (Feature::GetState, v(3, 10, 0), None),
+    (Feature::MaxOpVersion, v(3, 10, 0), None),
+    (Feature::Stripe, v(3, 0, 0), Some(v(6, 0, 0))),
+    (Feature::Tier, v(3, 7, 0), Some(v(6, 0, 0))),
+];
+
+const fn v(major: u32, minor: u32, patch: u32) -> GlusterVersion {
+    GlusterVersion {
+        major,
+        minor,
+        patch,
+    }
+}
+
+// `gluster --version` starts with a line like "glusterfs 10.1".  Older
+// releases add "built on <date>" after the version.
+fn parse_gluster_version(output_str: &str) -> Result<GlusterVersion, GlusterError> {
+    output_str
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("glusterfs "))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .next()
+        .ok_or_else(|| GlusterError::parse_failure(output_str, "gluster --version"))?
+        .parse()
+}
+
+/// The version of the installed gluster CLI
+/// # Failures
+/// Returns GlusterError if the command fails to run or its output isn't
+/// understood
+pub fn gluster_version() -> Result<GlusterVersion, GlusterError> {
+    GlusterClient::default().gluster_version()
+}
+
+/// The op-version the cluster is operating at
+/// # Failures
+/// Returns GlusterError if the command fails to run
+pub fn cluster_op_version() -> Result<u32, GlusterError> {
+    GlusterClient::default().cluster_op_version()
+}
+
+/// The highest op-version every peer in the cluster supports
+/// # Failures
+/// Returns GlusterError if the command fails to run
+pub fn max_op_version() -> Result<u32, GlusterError> {
+    GlusterClient::default().max_op_version()
+}
+
+impl GlusterClient {
+    /// The version of the gluster CLI.  It is looked up once per client and
+    /// shared by its clones.  With a remote host this is still the local
+    /// CLI, which is assumed to match the servers.
+    /// # Failures
+    /// Returns GlusterError if the command fails to run or its output isn't
+    /// understood
+    pub fn gluster_version(&self) -> Result<GlusterVersion, GlusterError> {
+        if let Some(version) = self.version.get() {
+            return Ok(*version);
+        }
+        let output = self.gluster_checked(&["--version"])?;
+        let version = parse_gluster_version(&String::from_utf8(output.stdout)?)?;
+        debug!("gluster version is {}", version);
+        Ok(*self.version.get_or_init(|| version))
+    }
+
+    /// Fail with GlusterError::Unsupported unless the gluster version has
+    /// `feature`
+    /// # Failures
+    /// Returns GlusterError::Unsupported if it doesn't or GlusterError if
+    /// the version can't be found out
+    pub fn require(&self, feature: Feature) -> Result<(), GlusterError> {
+        let version = self.gluster_version()?;
+        if version.supports(feature) {
+            Ok(())
+        } else {
+            Err(GlusterError::Unsupported { feature, version })
+        }
+    }
+
+    /// The op-version the cluster is operating at.  Features are only used
+    /// once every peer has been upgraded and this has been raised.
+    /// # Failures
+    /// Returns GlusterError if the command fails to run
+    pub fn cluster_op_version(&self) -> Result<u32, GlusterError> {
+        self.op_version_option("cluster.op-version")
+    }
+
+    /// The highest op-version every peer in the cluster supports.  This is
+    /// what cluster.op-version can be raised to.
+    /// # Failures
+    /// Returns GlusterError::Unsupported before Gluster 3.10 or
+    /// GlusterError if the command fails to run
+    pub fn max_op_version(&self) -> Result<u32, GlusterError> {
+        self.require(Feature::MaxOpVersion)?;
+        self.op_version_option("cluster.max-op-version")
+    }
+
+    fn op_version_option(&self, key: &str) -> Result<u32, GlusterError> {
+        let options = self.vol_get("all", key)?;
+        match options.iter().find(|o| o.0 == key) {
+            Some(option) => Ok(u32::from_str(option.1.trim())?),
+            None => Err(GlusterError::parse_failure(key, "volume get all")),
+        }
+    }
+}
+
+#[test]
+fn test_gluster_version() {
+    use executor::CannedExecutor;
+    use std::sync::Arc;
+
+    assert_eq!(
+        parse_gluster_version(
+            "glusterfs 10.1\nRepository revision: git://git.gluster.org/glusterfs.git\n"
+        )
+        .unwrap(),
+        GlusterVersion::new(10, 1, 0)
+    );
+    assert_eq!(
+        parse_gluster_version("glusterfs 3.7.6 built on Dec 25 2015 20:50:46\n").unwrap(),
+        GlusterVersion::new(3, 7, 6)
+    );
+    assert_eq!(
+        "9.4-1ubuntu1".parse::<GlusterVersion>().unwrap(),
+        GlusterVersion::new(9, 4, 0)
+    );
+    assert!(parse_gluster_version("gluster: command not found").is_err());
+    assert_eq!(GlusterVersion::new(3, 12, 15).op_version(), 31215);
+    assert_eq!(GlusterVersion::new(7, 2, 0).op_version(), 70200);
+
+    assert!(GlusterVersion::new(5, 13, 0).supports(Feature::Stripe));
+    assert!(!GlusterVersion::new(6, 0, 0).supports(Feature::Stripe));
+    assert!(!GlusterVersion::new(3, 8, 4).supports(Feature::GetState));
+    assert!(GlusterVersion::new(3, 10, 0).supports(Feature::GetState));
+
+    let canned = Arc::new(
+        CannedExecutor::new()
+            .respond(
+                &["volume", "get"],
+                0,
+                "<cliOutput><opRet>0</opRet><opErrno>0</opErrno><opErrstr/><volGetopts>\
+                 <count>1</count><Opt><Option>cluster.op-version</Option>\
+                 <Value>70200</Value></Opt></volGetopts></cliOutput>",
+                "",
+            )
+            .respond(&[], 0, "glusterfs 7.9\n", ""),
+    );
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    assert_eq!(client.cluster_op_version().unwrap(), 70200);
+    // The version is only asked for once
+    assert_eq!(
+        client.gluster_version().unwrap(),
+        GlusterVersion::new(7, 9, 0)
+    );
+    match client.clone().require(Feature::Tier) {
+        Err(GlusterError::Unsupported { feature, version }) => {
+            assert_eq!(feature, Feature::Tier);
+            assert_eq!(version.to_string(), "7.9.0");
+        }
+        other => panic!("expected Unsupported, got {:?}", other),
+    }
+    assert_eq!(
+        canned.calls(),
+        vec![
+            vec![
+                "gluster",
+                "--mode=script",
+                "volume",
+                "get",
+                "all",
+                "cluster.op-version",
+                "--xml",
+            ],
+            vec!["gluster", "--mode=script", "--version"],
+        ]
+    );
+}
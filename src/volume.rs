@@ -1,21 +1,17 @@
-extern crate serde;
-extern crate serde_xml_rs;
-
 use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Cursor};
+use std::io::Cursor;
 use std::net::IpAddr;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use super::{
-    process_output, resolve_to_ip, run_command, translate_to_bytes, BitrotOption, BrickStatus,
-    GlusterError, GlusterOption, Quota,
+    translate_to_bytes, xml_op_error, BitrotOption, BrickStatus, CliFailure, Feature,
+    GlusterClient, GlusterError, GlusterOption, Host, Quota,
 };
 use byteorder::{BigEndian, ReadBytesExt};
-use peer::{get_peer, Peer, State};
-use regex::Regex;
+use options::validate_all;
+use peer::{Peer, State};
 use rpc;
 use rpc::{Pack, UnPack};
 use unix_socket::UnixStream;
@@ -26,6 +22,8 @@ use uuid::Uuid;
 pub struct Brick {
     pub peer: Peer,
     pub path: PathBuf,
+    /// Arbiter bricks only hold metadata
+    pub is_arbiter: bool,
 }
 
 impl Brick {
@@ -39,6 +37,63 @@ impl Brick {
     }
 }
 
+impl FromStr for Brick {
+    type Err = GlusterError;
+
+    /// Parse a brick in gluster's host:/path form.  The host can be a
+    /// hostname, an IPv4 address or an IPv6 address with or without brackets
+    /// and the path can contain any character.  The peer's UUID is nil and its
+    /// state Unknown since neither can be told from the brick's name.
+    fn from_str(brick: &str) -> Result<Brick, GlusterError> {
+        let brick = brick.trim();
+        // Neither hostnames nor addresses can contain a /
+        let (host, path) = match brick.find(":/") {
+            Some(i) => (&brick[..i], &brick[i + 1..]),
+            None => return Err(GlusterError::parse_failure(brick, "brick host:/path")),
+        };
+        let host = match host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            Some(host) => host,
+            None => host,
+        };
+        if host.is_empty() {
+            return Err(GlusterError::parse_failure(brick, "brick host:/path"));
+        }
+        Ok(Brick {
+            peer: Peer::new(Uuid::nil(), Host::new(host), State::Unknown),
+            path: PathBuf::from(path),
+            is_arbiter: false,
+        })
+    }
+}
+
+#[test]
+fn test_brick_from_str() {
+    let brick =
+        Brick::from_str("ip-172-31-19-130.us-west-2.compute.internal:/bricks/brick-1").unwrap();
+    assert_eq!(
+        brick.peer.hostname,
+        "ip-172-31-19-130.us-west-2.compute.internal"
+    );
+    assert_eq!(brick.path, PathBuf::from("/bricks/brick-1"));
+    assert_eq!(brick.peer.uuid, Uuid::nil());
+
+    let brick = Brick::from_str("172.31.46.33:/mnt/xvdf").unwrap();
+    assert_eq!(brick.peer.hostname, "172.31.46.33");
+    assert_eq!(brick.to_string(), "172.31.46.33:/mnt/xvdf");
+
+    let brick = Brick::from_str("[2001:db8::1]:/srv/gluster/brick").unwrap();
+    assert_eq!(brick.peer.hostname, "2001:db8::1");
+    assert_eq!(brick.path, PathBuf::from("/srv/gluster/brick"));
+
+    let brick = Brick::from_str("fe80::1:/data/my brick:v2_ü").unwrap();
+    assert_eq!(brick.peer.hostname, "fe80::1");
+    assert_eq!(brick.path, PathBuf::from("/data/my brick:v2_ü"));
+
+    assert!(Brick::from_str("server1").is_err());
+    assert!(Brick::from_str("server1:brick").is_err());
+    assert!(Brick::from_str(":/brick").is_err());
+}
+
 impl fmt::Debug for Brick {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}:{:?}", self.peer.hostname, self.path.to_str())
@@ -84,10 +139,7 @@ impl FromStr for Transport {
             "tcp" => Ok(Transport::Tcp),
             "tcp,rdma" => Ok(Transport::TcpAndRdma),
             "rdma" => Ok(Transport::Rdma),
-            _ => Err(GlusterError::new(format!(
-                "Unknown transport string: {}",
-                s
-            ))),
+            _ => Err(GlusterError::parse_failure(s, "transport")),
         }
     }
 }
@@ -220,6 +272,168 @@ pub struct Volume {
     pub bricks: Vec<Brick>,
     /// A Vec containing a tuple of options that are configured on this Volume
     pub options: BTreeMap<String, String>,
+    /// Copies of the data kept by each replica set, arbiters included.
+    /// 1 if the volume isn't replicated
+    pub replica_count: u32,
+    /// Arbiter bricks in each replica set
+    pub arbiter_count: u32,
+    /// Bricks in each disperse set.  0 if the volume isn't dispersed
+    pub disperse_count: u32,
+    /// Bricks in each disperse set that can be lost without losing data
+    pub redundancy_count: u32,
+    /// Bricks in each distribute subvolume as glusterd counts them
+    pub dist_count: u32,
+    /// Bricks each file is striped over.  1 if the volume isn't striped
+    pub stripe_count: u32,
+    /// Snapshots taken of this volume
+    pub snapshot_count: u32,
+}
+
+impl Volume {
+    /// The number of bricks in each replica, disperse or stripe set
+    pub fn subvolume_size(&self) -> usize {
+        let size = if self.disperse_count > 0 {
+            self.disperse_count
+        } else {
+            self.replica_count.max(1) * self.stripe_count.max(1)
+        };
+        size as usize
+    }
+
+    /// The reconfigured options as GlusterOptions.  Options this library
+    /// doesn't know, or whose value it can't make sense of, are
+    /// GlusterOption::Unknown.
+    pub fn typed_options(&self) -> Vec<GlusterOption> {
+        self.options
+            .iter()
+            .map(|(key, value)| {
+                GlusterOption::from_str(key, value.clone()).unwrap_or_else(|e| {
+                    debug!("Unable to parse volume option {}: {}", key, e);
+                    GlusterOption::Unknown(key.clone(), value.clone())
+                })
+            })
+            .collect()
+    }
+
+    /// Splits the bricks into the replica or disperse sets that distribute
+    /// spreads files over.  Each set holds every copy or fragment of the files
+    /// stored on it.  Bricks are grouped in the order glusterd lists them
+    /// which is the order they were given in at create or add-brick time.
+    pub fn subvolumes(&self) -> Vec<&[Brick]> {
+        self.bricks.chunks(self.subvolume_size()).collect()
+    }
+}
+
+/// The counts making up a volume's layout
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Geometry {
+    pub(crate) replica_count: u32,
+    pub(crate) arbiter_count: u32,
+    pub(crate) disperse_count: u32,
+    pub(crate) redundancy_count: u32,
+    pub(crate) stripe_count: u32,
+}
+
+impl Default for Geometry {
+    fn default() -> Geometry {
+        Geometry {
+            replica_count: 1,
+            arbiter_count: 0,
+            disperse_count: 0,
+            redundancy_count: 0,
+            stripe_count: 1,
+        }
+    }
+}
+
+impl Geometry {
+    /// Parse the "Number of Bricks" line from `gluster volume info`:
+    /// "3", "2 x 3 = 6", "1 x (2 + 1) = 3", "1 x (4 + 2) = 6" or
+    /// "1 x 2 x 2 = 4"
+    fn from_brick_count(vol_type: &VolumeType, value: &str) -> Result<Geometry, GlusterError> {
+        let mut geometry = Geometry::default();
+        let layout = value.split('=').next().unwrap_or("");
+        let factors: Vec<&str> = layout.split(" x ").map(|f| f.trim()).collect();
+        match factors.len() {
+            1 => {}
+            2 => {
+                let set = factors[1];
+                if set.starts_with('(') {
+                    let parts: Vec<&str> = set
+                        .trim_matches(|c| c == '(' || c == ')')
+                        .split('+')
+                        .map(|p| p.trim())
+                        .collect();
+                    if parts.len() != 2 {
+                        return Err(GlusterError::parse_failure(value, "number of bricks"));
+                    }
+                    let data = u32::from_str(parts[0])?;
+                    let extra = u32::from_str(parts[1])?;
+                    match *vol_type {
+                        VolumeType::Disperse | VolumeType::DistributedAndDisperse => {
+                            geometry.disperse_count = data + extra;
+                            geometry.redundancy_count = extra;
+                        }
+                        _ => {
+                            geometry.replica_count = data + extra;
+                            geometry.arbiter_count = extra;
+                        }
+                    }
+                } else {
+                    match *vol_type {
+                        VolumeType::Stripe | VolumeType::DistributedAndStripe => {
+                            geometry.stripe_count = u32::from_str(set)?;
+                        }
+                        _ => geometry.replica_count = u32::from_str(set)?,
+                    }
+                }
+            }
+            3 => {
+                geometry.stripe_count = u32::from_str(factors[1])?;
+                geometry.replica_count = u32::from_str(factors[2])?;
+            }
+            _ => return Err(GlusterError::parse_failure(value, "number of bricks")),
+        }
+        Ok(geometry)
+    }
+
+    /// Bricks in each distribute subvolume
+    pub(crate) fn dist_count(&self) -> u32 {
+        if self.disperse_count > 0 {
+            self.disperse_count
+        } else {
+            self.replica_count * self.stripe_count
+        }
+    }
+}
+
+#[test]
+fn test_geometry_from_brick_count() {
+    let replicate = Geometry::from_brick_count(&VolumeType::Replicate, "1 x 2 = 2").unwrap();
+    assert_eq!(replicate.replica_count, 2);
+    assert_eq!(replicate.dist_count(), 2);
+
+    let arbiter =
+        Geometry::from_brick_count(&VolumeType::DistributedAndReplicate, "2 x (2 + 1) = 6")
+            .unwrap();
+    assert_eq!(arbiter.replica_count, 3);
+    assert_eq!(arbiter.arbiter_count, 1);
+
+    let disperse = Geometry::from_brick_count(&VolumeType::Disperse, "1 x (4 + 2) = 6").unwrap();
+    assert_eq!(disperse.disperse_count, 6);
+    assert_eq!(disperse.redundancy_count, 2);
+    assert_eq!(disperse.dist_count(), 6);
+
+    let striped =
+        Geometry::from_brick_count(&VolumeType::StripedAndReplicate, "1 x 2 x 2 = 4").unwrap();
+    assert_eq!(striped.stripe_count, 2);
+    assert_eq!(striped.replica_count, 2);
+    assert_eq!(striped.dist_count(), 4);
+
+    assert_eq!(
+        Geometry::from_brick_count(&VolumeType::Distribute, "3").unwrap(),
+        Geometry::default()
+    );
 }
 
 #[derive(Debug, Deserialize, Eq, PartialEq)]
@@ -227,7 +441,8 @@ pub struct BrickXml {
     pub name: String,
     #[serde(rename = "hostUuid")]
     pub host_uuid: Uuid,
-    #[serde(rename = "isArbiter")]
+    /// "1" for arbiter bricks.  Missing on Gluster versions without arbiters
+    #[serde(rename = "isArbiter", default)]
     pub is_arbiter: String,
 }
 
@@ -237,19 +452,21 @@ pub struct VolumeCliXml {
     pub ret: i32,
     #[serde(rename = "opErrno")]
     pub errno: i32,
-    #[serde(rename = "opErrStr")]
+    #[serde(rename = "opErrstr")]
     pub err_str: Option<String>,
-    #[serde(rename = "volInfo")]
+    // Failed commands may leave it out
+    #[serde(rename = "volInfo", default)]
     pub volumes: XmlVolumes,
 }
 
-#[derive(Debug, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
 pub struct XmlVolumes {
-    pub volumes: Blah,
+    pub volumes: XmlVolumeList,
 }
 
-#[derive(Debug, Deserialize, Eq, PartialEq)]
-pub struct Blah {
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct XmlVolumeList {
+    #[serde(default)]
     pub volume: Vec<VolumeXml>,
     pub count: u64,
 }
@@ -276,55 +493,200 @@ pub struct VolumeXml {
     #[serde(rename = "disperseCount")]
     pub disperse_count: String,
     #[serde(rename = "redundancyCount")]
-    redundancy_count: String,
+    pub redundancy_count: String,
     #[serde(rename = "type")]
     pub vol_type: String,
     #[serde(rename = "typeStr")]
-    pub type_str: VolumeType,
+    pub type_str: String,
     pub transport: String,
-    pub xlators: Option<String>,
-    pub bricks: Vec<String>,
+    pub bricks: XmlBricks,
     #[serde(rename = "optCount")]
     pub option_count: String,
+    #[serde(default)]
+    pub options: XmlOptions,
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct XmlBricks {
+    #[serde(default)]
+    pub brick: Vec<BrickXml>,
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct XmlOptions {
+    #[serde(default)]
+    pub option: Vec<OptionXml>,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct OptionXml {
+    pub name: String,
+    pub value: String,
+}
+
+impl VolumeXml {
+    /// Convert into a Volume.  Each brick's Peer is looked up in `peers` by
+    /// the host UUID glusterd reported for it.  Bricks on hosts missing from
+    /// `peers` get a Peer with an Unknown state.
+    pub fn into_volume(self, peers: &[Peer]) -> Result<Volume, GlusterError> {
+        let mut bricks: Vec<Brick> = Vec::new();
+        for brick in self.bricks.brick {
+            let mut parsed = Brick::from_str(&brick.name)?;
+            parsed.peer = match peers.iter().find(|p| p.uuid == brick.host_uuid) {
+                Some(peer) => peer.clone(),
+                None => Peer {
+                    uuid: brick.host_uuid,
+                    ..parsed.peer
+                },
+            };
+            parsed.is_arbiter = brick.is_arbiter.trim() == "1";
+            bricks.push(parsed);
+        }
+        let options: BTreeMap<String, String> = self
+            .options
+            .option
+            .into_iter()
+            .map(|o| (o.name, o.value))
+            .collect();
+        Ok(Volume {
+            name: self.name,
+            vol_type: VolumeType::from_str(&self.type_str),
+            id: self.id,
+            status: self.status_str,
+            transport: Transport::from_str(self.transport.trim())?,
+            bricks,
+            options,
+            replica_count: u32::from_str(self.replica_count.trim())?,
+            arbiter_count: u32::from_str(self.arbiter_count.trim())?,
+            disperse_count: u32::from_str(self.disperse_count.trim())?,
+            redundancy_count: u32::from_str(self.redundancy_count.trim())?,
+            dist_count: u32::from_str(self.dist_count.trim())?,
+            stripe_count: u32::from_str(self.stripe_count.trim())?,
+            snapshot_count: u32::from_str(self.snapshot_count.trim())?,
+        })
+    }
+}
+
+/// Parse the output of `gluster volume info --xml`
+fn parse_volume_info_xml(
+    output_str: &str,
+    argv: &[String],
+) -> Result<Vec<VolumeXml>, GlusterError> {
+    let cli_output: VolumeCliXml = serde_xml_rs::from_str(output_str)?;
+    if cli_output.ret != 0 {
+        return Err(xml_op_error(
+            argv,
+            cli_output.ret,
+            cli_output.errno,
+            cli_output.err_str,
+        ));
+    }
+    Ok(cli_output.volumes.volumes.volume)
 }
 
 #[test]
 fn test_parse_volume_info_xml() {
+    use super::{SplitBrainPolicy, Toggle};
     use std::fs::File;
     use std::io::Read;
 
-    /*
     let test_data = {
         let mut f = File::open("tests/volume_info.xml").unwrap();
         let mut s = String::new();
         f.read_to_string(&mut s).unwrap();
         s
     };
-    let result: VolumeCliXml = deserialize(test_data.as_bytes()).unwrap();
-    println!("vol_info_xml: {:?}", result);
-    */
-    /*
-    let vol_info = VolumeXml {
-        name: "test".to_string(),
-        vol_type: VolumeType::Replicate,
-        id: Uuid::parse_str("cae6868d-b080-4ea3-927b-93b5f1e3fe69").unwrap(),
-        status: "Started".to_string(),
-        transport: Transport::Tcp,
-        bricks: vec![
-            Brick {
-                peer: Peer {
-                    uuid: Uuid::parse_str("78f68270-201a-4d8a-bad3-7cded6e6b7d8").unwrap(),
-                    hostname: "test_ip".to_string(),
-                    status: State::Connected,
-                },
-                path: PathBuf::from("/mnt/xvdf"),
-            },
-        ],
-        options: options_map,
-    };
-    */
-    //println!("vol_info_xml: {:?}", result);
-    //assert_eq!(vol_info, result);
+    let peers = vec![Peer::new(
+        Uuid::parse_str("fc0924ad-62dd-4d63-8731-daad937a3006").unwrap(),
+        Host::new("10.144.73.12"),
+        State::PeerInCluster,
+    )];
+    let mut result = parse_volume_info_xml(&test_data, &[]).unwrap();
+    assert_eq!(result.len(), 1);
+
+    let vol = result.remove(0).into_volume(&peers).unwrap();
+    assert_eq!(vol.name, "gv0");
+    assert_eq!(vol.vol_type, VolumeType::DistributedAndReplicate);
+    assert_eq!(
+        vol.id,
+        Uuid::parse_str("175ddabd-ea14-4dbf-b797-0f8801e4eb95").unwrap()
+    );
+    assert_eq!(vol.status, "Started");
+    assert_eq!(vol.transport, Transport::Tcp);
+    assert_eq!(vol.bricks.len(), 24);
+    assert_eq!(
+        vol.bricks[0],
+        Brick {
+            peer: peers[0].clone(),
+            path: PathBuf::from("/mnt/sdb/brick"),
+            is_arbiter: false,
+        }
+    );
+    // Not in the peer list so the state isn't known
+    assert_eq!(
+        vol.bricks[1].peer,
+        Peer::new(
+            Uuid::parse_str("bd7db636-ec69-4973-b6ab-505c4af3805d").unwrap(),
+            Host::new("10.144.73.13"),
+            State::Unknown,
+        )
+    );
+    assert_eq!(vol.options.len(), 7);
+    assert_eq!(
+        vol.options.get("features.scrub"),
+        Some(&"Active".to_string())
+    );
+    assert_eq!(
+        vol.typed_options(),
+        vec![
+            GlusterOption::FavoriteChildPolicy(SplitBrainPolicy::Mtime),
+            GlusterOption::Unknown("features.bitrot".to_string(), "on".to_string()),
+            GlusterOption::Unknown("features.scrub".to_string(), "Active".to_string()),
+            GlusterOption::NfsDisable(Toggle::On),
+            GlusterOption::PerformanceParallelReadDir(Toggle::On),
+            GlusterOption::PerformanceReadDirAhead(Toggle::On),
+            GlusterOption::Unknown("transport.address-family".to_string(), "inet".to_string()),
+        ]
+    );
+
+    let missing = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cliOutput>
+  <opRet>-1</opRet>
+  <opErrno>30800</opErrno>
+  <opErrstr>Volume missing does not exist</opErrstr>
+  <volInfo>
+    <volumes>
+      <count>0</count>
+    </volumes>
+  </volInfo>
+</cliOutput>"#;
+    match parse_volume_info_xml(missing, &[]) {
+        Err(GlusterError::VolumeNotFound(ref volume)) => assert_eq!(volume, "missing"),
+        other => panic!("expected VolumeNotFound, got {:?}", other),
+    }
+
+    // Lock contention is classified so that it can be retried
+    let locked = "<cliOutput><opRet>-1</opRet><opErrno>30800</opErrno>\
+                  <opErrstr>Another transaction is in progress for gv0. \
+                  Please try again after some time.</opErrstr></cliOutput>";
+    let argv = vec![
+        "gluster".to_string(),
+        "volume".to_string(),
+        "info".to_string(),
+    ];
+    match parse_volume_info_xml(locked, &argv) {
+        Err(GlusterError::CommandFailed {
+            argv: ref failed,
+            exit_code,
+            ref kind,
+            ..
+        }) => {
+            assert_eq!(failed, &argv);
+            assert_eq!(exit_code, Some(-1));
+            assert_eq!(*kind, CliFailure::TransactionInProgress);
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
 }
 
 // Volume Name: test
@@ -351,83 +713,10 @@ enum ParseState {
 
 /// Lists all available volume names.
 /// # Failures
-/// Will return None if the Volume list command failed or if volume could not
-/// be transformed
-/// into a String from utf8
-pub fn volume_list() -> Option<Vec<String>> {
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("volume".to_string());
-    arg_list.push("list".to_string());
-    let output = run_command("gluster", &arg_list, true, false);
-    let status = output.status;
-
-    if !status.success() {
-        debug!("Volume list get command failed");
-        return None;
-    }
-    let output_str: String = match String::from_utf8(output.stdout) {
-        Ok(n) => n,
-        Err(_) => {
-            debug!("Volume list output transformation to utf8 failed");
-            return None;
-        }
-    };
-    let mut volume_names: Vec<String> = Vec::new();
-    for line in output_str.lines() {
-        if line.is_empty() {
-            // Skip any blank lines in the output
-            continue;
-        }
-        volume_names.push(line.trim().to_string());
-    }
-    Some(volume_names)
-}
-
-#[test]
-fn test_parse_volume_info2() {
-    let _test_data = r#"
-    type=2
-count=9
-status=1
-sub_count=3
-stripe_count=1
-replica_count=3
-disperse_count=0
-redundancy_count=0
-version=9
-transport-type=0
-volume-id=e7d940ba-8b7c-4e37-a664-2975bc8452fc
-username=b2aa4fe8-5c35-4bc0-8666-a8964e6ec884
-password=4f377e97-554e-4bd5-8c58-34c3b9074db8
-op-version=31000
-client-op-version=30712
-quota-version=1
-tier-enabled=0
-parent_volname=N/A
-restored_from_snap=00000000-0000-0000-0000-000000000000
-snap-max-hard-limit=256
-performance.client-io-threads=on
-nfs.disable=on
-transport.address-family=inet
-server.allow-insecure=on
-features.quota=on
-features.inode-quota=on
-features.quota-deem-statfs=on
-performance.readdir-ahead=on
-performance.parallel-readdir=on
-cluster.favorite-child-policy=mtime
-features.bitrot=on
-features.scrub=Active
-brick-0=10.0.2.81:-mnt-sdc-brick
-brick-1=10.0.2.82:-mnt-sdc-brick
-brick-2=10.0.2.83:-mnt-sdc-brick
-brick-3=10.0.2.81:-mnt-sdd-brick
-brick-4=10.0.2.82:-mnt-sdd-brick
-brick-5=10.0.2.83:-mnt-sdd-brick
-brick-6=10.0.2.81:-mnt-sde-brick
-brick-7=10.0.2.82:-mnt-sde-brick
-brick-8=10.0.2.83:-mnt-sde-brick
-    "#;
+/// Will return GlusterError if the Volume list command failed or if its
+/// output could not be transformed into a String from utf8
+pub fn volume_list() -> Result<Vec<String>, GlusterError> {
+    GlusterClient::default().volume_list()
 }
 
 #[test]
@@ -449,7 +738,20 @@ transport.address-family: inet
 performance.readdir-ahead: on
 nfs.disable: on
 "#;
-    let result = parse_volume_info("test", test_data).unwrap();
+    let client = GlusterClient::builder()
+        .executor(::std::sync::Arc::new(
+            ::executor::CannedExecutor::new().respond(
+                &["pool", "list"],
+                0,
+                &::peer::pool_list_xml(&[(
+                    "78f68270-201a-4d8a-bad3-7cded6e6b7d8",
+                    "172.31.41.135",
+                )]),
+                "",
+            ),
+        ))
+        .build();
+    let result = parse_volume_info(&client, "test", test_data).unwrap();
     let mut options_map: BTreeMap<String, String> = BTreeMap::new();
     options_map.insert("features.inode-quota".to_string(), "off".to_string());
     options_map.insert("features.quota".to_string(), "off".to_string());
@@ -465,55 +767,109 @@ nfs.disable: on
         transport: Transport::Tcp,
         bricks: vec![Brick {
             peer: Peer {
-                uuid: Uuid::parse_str("78f68270-201a-4d8a-bad3-7cded6e6b7d8").unwrap(),
-                hostname: "test_ip".to_string(),
-                status: State::Connected,
+                connected: true,
+                state_code: Some(3),
+                state_text: "Peer in Cluster".to_string(),
+                ..Peer::new(
+                    Uuid::parse_str("78f68270-201a-4d8a-bad3-7cded6e6b7d8").unwrap(),
+                    Host::new("172.31.41.135"),
+                    State::PeerInCluster,
+                )
             },
             path: PathBuf::from("/mnt/xvdf"),
+            is_arbiter: false,
         }],
         options: options_map,
+        replica_count: 2,
+        arbiter_count: 0,
+        disperse_count: 0,
+        redundancy_count: 0,
+        dist_count: 2,
+        stripe_count: 1,
+        snapshot_count: 0,
     };
     println!("vol_info: {:?}", vol_info);
     assert_eq!(vol_info, result);
 }
 
-// Advantages: Faster
-// Disadvantages: Needs to be run on a gluster server
-fn parse_volume_info2(volume: &str) -> Result<Volume, GlusterError> {
-    let mut p = PathBuf::from("/var/lib/glusterd/vols");
-    p.push(volume);
-    p.push("info");
+/// Finds the Peer serving each brick.  The pool is only listed once and each
+/// host only looked up once however many bricks share them.
+struct BrickPeers<'a> {
+    client: &'a GlusterClient,
+    peers: Option<Vec<Peer>>,
+    local_ips: Option<Vec<IpAddr>>,
+    found: HashMap<Host, Peer>,
+}
 
-    let f = File::open(p)?;
-    let f = BufReader::new(f);
+impl<'a> BrickPeers<'a> {
+    fn new(client: &'a GlusterClient) -> BrickPeers<'a> {
+        BrickPeers {
+            client,
+            peers: None,
+            local_ips: None,
+            found: HashMap::new(),
+        }
+    }
 
-    let name = String::new();
-    let status = String::new();
-    let _vol_type = String::new();
-    let mut transport = String::new();
-    let options: BTreeMap<String, String> = BTreeMap::new();
-    let bricks: Vec<Brick> = Vec::new();
+    /// The peer serving bricks on `host`, named the way the brick names it
+    fn peer(&mut self, host: &Host) -> Result<Peer, GlusterError> {
+        if let Some(peer) = self.found.get(host) {
+            return Ok(peer.clone());
+        }
+        if self.peers.is_none() {
+            self.peers = Some(self.client.peer_list()?);
+        }
+        let peer = match self.find(host)? {
+            Some(peer) => Peer {
+                hostname: host.clone(),
+                ..peer
+            },
+            None => return Err(GlusterError::PeerNotFound(host.to_string())),
+        };
+        self.found.insert(host.clone(), peer.clone());
+        Ok(peer)
+    }
 
-    for line in f.lines() {
-        let line = line?;
-        if line.starts_with("transport-type") {
-            transport = line.split('=').collect::<Vec<&str>>()[1].to_string();
+    fn find(&mut self, host: &Host) -> Result<Option<Peer>, GlusterError> {
+        let peers = self.peers.as_ref().map_or(&[][..], |p| &p[..]);
+        if let Some(peer) = peers.iter().find(|peer| peer.is_known_as(host)) {
+            return Ok(Some(peer.clone()));
+        }
+        // The brick may name its server differently than the pool does
+        let resolver = self.client.resolver();
+        let addresses = host.addresses(resolver)?;
+        for peer in peers {
+            // The pool lists this server as localhost
+            let found = if peer.hostname == "localhost" {
+                if self.local_ips.is_none() {
+                    self.local_ips = Some(self.client.get_local_ips()?);
+                }
+                let local_ips = self.local_ips.as_ref().map_or(&[][..], |l| &l[..]);
+                addresses.iter().any(|a| local_ips.contains(a))
+            } else {
+                match peer.hostname.addresses(resolver) {
+                    Ok(peer_addresses) => peer_addresses.iter().any(|a| addresses.contains(a)),
+                    Err(e) => {
+                        debug!("Skipping peer {}: {}", peer.hostname, e);
+                        false
+                    }
+                }
+            };
+            if found {
+                return Ok(Some(peer.clone()));
+            }
         }
+        Ok(None)
     }
-    Ok(Volume {
-        name,
-        vol_type: VolumeType::from_str(""),
-        id: Uuid::from_str("")?,
-        status,
-        transport: Transport::from_str(&transport)?,
-        bricks,
-        options,
-    })
 }
 
 // Advantages: Can be run from anywhere with gluster commands installed
 // Disadvantages: Slower and prone to CLI breakage
-fn parse_volume_info(volume: &str, output_str: &str) -> Result<Volume, GlusterError> {
+fn parse_volume_info(
+    client: &GlusterClient,
+    volume: &str,
+    output_str: &str,
+) -> Result<Volume, GlusterError> {
     // Variables we will return in a struct
     let mut transport_type = String::new();
     let mut volume_type = String::new();
@@ -522,6 +878,9 @@ fn parse_volume_info(volume: &str, output_str: &str) -> Result<Volume, GlusterEr
     let mut status = String::new();
     let mut bricks: Vec<Brick> = Vec::new();
     let mut id = Uuid::nil();
+    let mut brick_count = String::new();
+    let mut snapshot_count = 0;
+    let mut brick_peers = BrickPeers::new(client);
 
     if output_str.trim() == "No volumes present" {
         debug!("No volumes present");
@@ -532,10 +891,7 @@ fn parse_volume_info(volume: &str, output_str: &str) -> Result<Volume, GlusterEr
     if output_str.trim() == format!("Volume {} does not exist", volume) {
         debug!("Volume {} does not exist", volume);
         println!("Volume {} does not exist", volume);
-        return Err(GlusterError::new(format!(
-            "Volume: {} does not exist",
-            volume
-        )));
+        return Err(GlusterError::VolumeNotFound(volume.to_string()));
     }
 
     let mut parser_state = ParseState::Root;
@@ -581,49 +937,25 @@ fn parse_volume_info(volume: &str, output_str: &str) -> Result<Volume, GlusterEr
                 if name == "Transport-Type" {
                     transport_type = value.to_owned();
                 }
-                if name == "Number of Bricks" {}
+                if name == "Number of Bricks" {
+                    brick_count = value.to_owned();
+                }
+                if name == "Snapshot Count" {
+                    snapshot_count = u32::from_str(value.trim())?;
+                }
             }
             ParseState::Bricks => {
-                let parts: Vec<String> = line.split(": ").map(|e| e.to_string()).collect();
-                if parts.len() < 2 {
+                let value = match line.split_once(": ") {
+                    Some((_, value)) => value,
                     // We don't know what this is
-                    continue;
-                }
-                let value = &parts[1];
-
-                // let brick_str = value;
-                let brick_parts: Vec<&str> = value.split(':').collect();
-                assert!(
-                    brick_parts.len() == 2,
-                    "Failed to parse bricks from gluster vol info"
-                );
-
-                let mut hostname = brick_parts[0].trim().to_string();
-
-                // Translate back into an IP address if needed
-                let check_for_ip = hostname.parse::<IpAddr>();
-
-                if check_for_ip.is_err() {
-                    // It's a hostname so lets resolve it
-                    hostname = match resolve_to_ip(&hostname) {
-                        Ok(ip_addr) => ip_addr,
-                        Err(e) => {
-                            return Err(GlusterError::new(format!(
-                                "Failed to resolve hostname: \
-                                 {}. Error: {}",
-                                &hostname, e
-                            )));
-                        }
-                    };
-                }
-
-                let peer: Peer = get_peer(&hostname.to_string())?;
-                debug!("get_peer_by_ipaddr result: Peer: {:?}", peer);
-                let brick = Brick {
-                    // Should this panic if it doesn't work?
-                    peer,
-                    path: PathBuf::from(brick_parts[1].to_string()),
+                    None => continue,
                 };
+                // Arbiter bricks are listed as Brick3: host:/path (arbiter)
+                let is_arbiter = value.ends_with(" (arbiter)");
+                let mut brick = Brick::from_str(value.trim_end_matches(" (arbiter)"))?;
+                brick.peer = brick_peers.peer(&brick.peer.hostname)?;
+                debug!("get_peer_by_ipaddr result: Peer: {:?}", brick.peer);
+                brick.is_arbiter = is_arbiter;
                 bricks.push(brick);
             }
             ParseState::Options => {
@@ -640,6 +972,7 @@ fn parse_volume_info(volume: &str, output_str: &str) -> Result<Volume, GlusterEr
 
     let transport = Transport::new(&transport_type);
     let vol_type = VolumeType::new(&volume_type);
+    let geometry = Geometry::from_brick_count(&vol_type, &brick_count)?;
     let vol_info = Volume {
         name: volume_name,
         vol_type,
@@ -648,6 +981,13 @@ fn parse_volume_info(volume: &str, output_str: &str) -> Result<Volume, GlusterEr
         transport,
         bricks,
         options: volume_options,
+        replica_count: geometry.replica_count,
+        arbiter_count: geometry.arbiter_count,
+        disperse_count: geometry.disperse_count,
+        redundancy_count: geometry.redundancy_count,
+        dist_count: geometry.dist_count(),
+        stripe_count: geometry.stripe_count,
+        snapshot_count,
     };
     Ok(vol_info)
 }
@@ -656,27 +996,238 @@ fn parse_volume_info(volume: &str, output_str: &str) -> Result<Volume, GlusterEr
 /// # Failures
 /// Will return GlusterError if the command failed to run.
 pub fn volume_info(volume: &str) -> Result<Volume, GlusterError> {
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("volume".to_string());
-    arg_list.push("info".to_string());
-    arg_list.push(volume.to_string());
-    let output = run_command("gluster", &arg_list, true, false);
-    let status = output.status;
-
-    if !status.success() {
-        debug!("Volume info get command failed");
-        println!(
-            "Volume info get command failed with error: {}",
-            String::from_utf8_lossy(&output.stderr)
-        );
+    GlusterClient::default().volume_info(volume)
+}
 
-        // TODO: What is the appropriate error to report here?
-        // The client is using this to figure out if it should make a volume
-        return Err(GlusterError::NoVolumesPresent);
-    }
-    let output_str: String = String::from_utf8(output.stdout)?;
+/// Returns every Volume in the cluster with all available information
+/// # Failures
+/// Will return GlusterError if the command failed to run.
+pub fn volume_info_all() -> Result<Vec<Volume>, GlusterError> {
+    GlusterClient::default().volume_info_all()
+}
+
+#[test]
+fn test_volume_info_all() {
+    use executor::CannedExecutor;
+    use std::fs::File;
+    use std::io::Read;
+    use std::sync::Arc;
+
+    let xml = {
+        let mut f = File::open("tests/volume_info_all.xml").unwrap();
+        let mut s = String::new();
+        f.read_to_string(&mut s).unwrap();
+        s
+    };
+    let canned = Arc::new(
+        CannedExecutor::new()
+            .respond(&["volume", "info", "all"], 0, &xml, "")
+            .respond(&["pool", "list"], 0, &::peer::pool_list_xml(&[]), "")
+            .respond(&["volume", "list"], 0, "gv0\ntest\n", ""),
+    );
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    let volumes = client.volume_info_all().unwrap();
+    assert_eq!(volumes.len(), 2);
+    assert_eq!(volumes[0].name, "gv0");
+    assert_eq!(volumes[0].vol_type, VolumeType::Replicate);
+    assert_eq!(volumes[0].bricks.len(), 3);
+    assert_eq!(volumes[1].name, "test");
+    assert_eq!(volumes[1].vol_type, VolumeType::Disperse);
+    assert_eq!(volumes[1].disperse_count, 3);
+    assert_eq!(volumes[1].redundancy_count, 1);
+    assert_eq!(volumes[1].subvolumes().len(), 1);
+    assert_eq!(
+        canned.calls(),
+        vec![
+            vec!["gluster", "--mode=script", "volume", "info", "all", "--xml"],
+            vec!["gluster", "--mode=script", "pool", "list", "--xml"],
+        ]
+    );
+
+    assert_eq!(
+        client.volume_list().unwrap(),
+        vec!["gv0".to_string(), "test".to_string()]
+    );
+    let client = GlusterClient::builder()
+        .executor(Arc::new(CannedExecutor::new().respond(
+            &["volume", "list"],
+            0,
+            "No volumes present in cluster\n",
+            "",
+        )))
+        .build();
+    assert_eq!(client.volume_list().unwrap(), Vec::<String>::new());
+}
+
+#[test]
+fn test_volume_info() {
+    use executor::CannedExecutor;
+    use std::fs::File;
+    use std::io::Read;
+    use std::sync::Arc;
+
+    let xml = {
+        let mut f = File::open("tests/volume_info.xml").unwrap();
+        let mut s = String::new();
+        f.read_to_string(&mut s).unwrap();
+        s
+    };
+    let canned = Arc::new(
+        CannedExecutor::new()
+            .respond(&["volume", "info", "gv0"], 0, &xml, "")
+            .respond(
+                &["pool", "list"],
+                0,
+                &::peer::pool_list_xml(&[("fc0924ad-62dd-4d63-8731-daad937a3006", "10.144.73.12")]),
+                "",
+            ),
+    );
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    let vol = client.volume_info("gv0").unwrap();
+    assert_eq!(vol.name, "gv0");
+    assert_eq!(vol.bricks.len(), 24);
+    assert_eq!(vol.bricks[0].peer.status, State::PeerInCluster);
+    assert_eq!(
+        canned.calls(),
+        vec![
+            vec!["gluster", "--mode=script", "volume", "info", "gv0", "--xml"],
+            vec!["gluster", "--mode=script", "pool", "list", "--xml"],
+        ]
+    );
+
+    // Output that isn't xml is handed to the text parser instead
+    let canned = Arc::new(
+        CannedExecutor::new()
+            .respond(
+                &["volume", "info", "test"],
+                0,
+                "Volume Name: test\nType: Replicate\n\
+                 Volume ID: cae6868d-b080-4ea3-927b-93b5f1e3fe69\nStatus: Started\n\
+                 Transport-type: tcp\nBricks:\nBrick1: 172.31.41.135:/mnt/xvdf\n\
+                 Brick2: 172.31.41.136:/mnt/xvdf (arbiter)\n",
+                "",
+            )
+            .respond(
+                &["pool", "list"],
+                0,
+                &::peer::pool_list_xml(&[
+                    ("78f68270-201a-4d8a-bad3-7cded6e6b7d8", "172.31.41.135"),
+                    ("afbd338e-881b-4557-8764-52e259885ca3", "172.31.41.136"),
+                ]),
+                "",
+            ),
+    );
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    let vol = client.volume_info("test").unwrap();
+    assert_eq!(vol.name, "test");
+    assert_eq!(vol.bricks.len(), 2);
+    assert!(!vol.bricks[0].is_arbiter);
+    assert!(vol.bricks[1].is_arbiter);
+    assert_eq!(vol.bricks[1].path, PathBuf::from("/mnt/xvdf"));
+    assert_eq!(canned.calls().len(), 3);
+}
+
+#[test]
+fn test_volume_info_brick_hosts() {
+    use executor::CannedExecutor;
+    use host::CannedResolver;
+    use std::sync::Arc;
+
+    let canned = Arc::new(
+        CannedExecutor::new()
+            .respond(
+                &["volume", "info", "test"],
+                0,
+                "Volume Name: test\nType: Distribute\n\
+                 Volume ID: cae6868d-b080-4ea3-927b-93b5f1e3fe69\nStatus: Started\n\
+                 Transport-type: tcp\nBricks:\n\
+                 Brick1: gluster-1.example.com:/bricks/b1\n\
+                 Brick2: 10.0.0.2:/bricks/b1\n\
+                 Brick3: gluster-3.example.com:/bricks/b1\n\
+                 Brick4: gluster-1.example.com:/bricks/b2\n",
+                "",
+            )
+            .respond(
+                &["pool", "list"],
+                0,
+                &::peer::pool_list_xml(&[
+                    ("78f68270-201a-4d8a-bad3-7cded6e6b7d8", "10.0.0.1"),
+                    ("afbd338e-881b-4557-8764-52e259885ca3", "10.0.0.2"),
+                    (
+                        "5f45e89a-23c1-41dd-b0cd-fd9cf37f1520",
+                        "gluster-3.example.com",
+                    ),
+                ]),
+                "",
+            ),
+    );
+    let resolver = Arc::new(
+        CannedResolver::new()
+            .host("gluster-1.example.com", &["10.0.0.1"])
+            .host("gluster-3.example.com", &["10.0.0.3"]),
+    );
+    let client = GlusterClient::builder()
+        .executor(canned)
+        .resolver(resolver.clone())
+        .build();
+    let vol = client.volume_info("test").unwrap();
+
+    // Bricks keep the name they were created with but belong to the peer
+    // with the same address
+    let uuids: Vec<String> = vol
+        .bricks
+        .iter()
+        .map(|b| b.peer.uuid.to_hyphenated().to_string()[..8].to_string())
+        .collect();
+    assert_eq!(uuids, vec!["78f68270", "afbd338e", "5f45e89a", "78f68270"]);
+    assert_eq!(vol.bricks[0].peer.hostname, "gluster-1.example.com");
+    assert_eq!(
+        vol.bricks[0].to_string(),
+        "gluster-1.example.com:/bricks/b1"
+    );
+    // Addresses and names the pool already uses are never looked up
+    assert_eq!(resolver.lookups(), vec!["gluster-1.example.com"]);
+}
+
+#[test]
+fn test_volume_info_command_count() {
+    use executor::CannedExecutor;
+    use std::sync::Arc;
+
+    let pool_list = ::peer::pool_list_xml(&[
+        ("78f68270-201a-4d8a-bad3-7cded6e6b7d8", "10.0.0.1"),
+        ("afbd338e-881b-4557-8764-52e259885ca3", "10.0.0.2"),
+        ("5f45e89a-23c1-41dd-b0cd-fd9cf37f1520", "10.0.0.3"),
+    ]);
+    // Count the commands run to read the text output of a volume with
+    // brick_count bricks spread over 3 servers
+    let commands_run = |brick_count: usize| {
+        let mut output = "Volume Name: test\nType: Distributed-Replicate\n\
+                          Volume ID: cae6868d-b080-4ea3-927b-93b5f1e3fe69\n\
+                          Status: Started\nTransport-type: tcp\nBricks:\n"
+            .to_string();
+        for i in 0..brick_count {
+            output.push_str(&format!(
+                "Brick{}: 10.0.0.{}:/bricks/{}\n",
+                i + 1,
+                i % 3 + 1,
+                i / 3
+            ));
+        }
+        let canned = Arc::new(
+            CannedExecutor::new()
+                .respond(&["volume", "info", "test"], 0, &output, "")
+                .respond(&["pool", "list"], 0, &pool_list, ""),
+        );
+        let client = GlusterClient::builder().executor(canned.clone()).build();
+        let vol = client.volume_info("test").unwrap();
+        assert_eq!(vol.bricks.len(), brick_count);
+        canned.calls().len()
+    };
 
-    parse_volume_info(&volume, &output_str)
+    // volume info --xml, volume info and pool list whatever the brick count
+    assert_eq!(commands_run(3), 3);
+    assert_eq!(commands_run(60), 3);
 }
 
 /// Returns a u64 representing the bytes used on the volume.
@@ -688,121 +1239,14 @@ pub fn volume_info(volume: &str) -> Result<Volume, GlusterError> {
 /// # Failures
 /// Will return GlusterError if the RPC fails
 pub fn get_quota_usage(volume: &str) -> Result<u64, GlusterError> {
-    let xid = 1; //Transaction ID number.
-    let prog = rpc::GLUSTER_QUOTA_PROGRAM_NUMBER;
-    let vers = 1; //RPC version == 1
-
-    let verf = rpc::GlusterAuth {
-        flavor: rpc::AuthFlavor::AuthNull,
-        stuff: vec![0, 0, 0, 0],
-    };
-    let verf_bytes = verf.pack()?;
-
-    let creds = rpc::GlusterCred {
-        flavor: rpc::GLUSTER_V2_CRED_FLAVOR,
-        pid: 0,
-        uid: 0,
-        gid: 0,
-        groups: "".to_string(),
-        lock_owner: vec![0, 0, 0, 0],
-    };
-    let cred_bytes = creds.pack()?;
-
-    let mut call_bytes = rpc::pack_quota_callheader(
-        xid,
-        prog,
-        vers,
-        rpc::GlusterAggregatorCommand::GlusterAggregatorGetlimit,
-        cred_bytes,
-        verf_bytes,
-    )?;
-
-    let mut dict: HashMap<String, Vec<u8>> = HashMap::with_capacity(4);
-
-    // TODO: Make a Gluster wd RPC call and parse this from the quota.conf file
-    // This is crap
-    let mut gfid = "00000000-0000-0000-0000-000000000001"
-        .to_string()
-        .into_bytes();
-    gfid.push(0); //Null Terminate
-    let mut name = volume.to_string().into_bytes();
-    name.push(0); //Null Terminate
-    let mut version = "1.20000005".to_string().into_bytes();
-    version.push(0); //Null Terminate
-                     //No idea what vol_type == 5 means to Gluster
-    let mut vol_type = "5".to_string().into_bytes();
-    vol_type.push(0); //Null Terminate
-
-    dict.insert("gfid".to_string(), gfid);
-    dict.insert("type".to_string(), vol_type);
-    dict.insert("volume-uuid".to_string(), name);
-    dict.insert("version".to_string(), version);
-    let quota_request = rpc::GlusterCliRequest { dict };
-    let quota_bytes = quota_request.pack()?;
-    for byte in quota_bytes {
-        call_bytes.push(byte);
-    }
-
-    // Ok.. we need to hunt down the quota socket file ..crap..
-    let addr = Path::new("/var/run/gluster/quotad.socket");
-    let mut sock = UnixStream::connect(&addr)?;
-
-    let _send_bytes = rpc::sendrecord(&mut sock, &call_bytes)?;
-    let mut reply_bytes = rpc::recvrecord(&mut sock)?;
-
-    let mut cursor = Cursor::new(&mut reply_bytes[..]);
-
-    // Check for success
-    rpc::unpack_replyheader(&mut cursor)?;
-
-    let mut cli_response = rpc::GlusterCliResponse::unpack(&mut cursor)?;
-    // The raw bytes
-    let quota_size_bytes = match cli_response.dict.get_mut("trusted.glusterfs.quota.size") {
-        Some(s) => s,
-        None => {
-            return Err(GlusterError::new(
-                "trusted.glusterfs.quota.size was not returned from \
-                 quotad"
-                    .to_string(),
-            ));
-        }
-    };
-    // Gluster is crazy and encodes a ton of data in this vector.  We're just going
-    // to
-    // read the first value and throw away the rest.  Why they didn't just use a
-    // struct and
-    // XDR is beyond me
-    let mut size_cursor = Cursor::new(&mut quota_size_bytes[..]);
-    let usage = size_cursor.read_u64::<BigEndian>()?;
-    Ok(usage)
+    GlusterClient::default().get_quota_usage(volume)
 }
 
 /// Return a list of quotas on the volume if any
 /// # Failures
 /// Will return GlusterError if the command failed to run.
 pub fn quota_list(volume: &str) -> Result<Vec<Quota>, GlusterError> {
-    let mut args_list: Vec<String> = Vec::new();
-    args_list.push("volume".to_string());
-    args_list.push("quota".to_string());
-    args_list.push(volume.to_string());
-    args_list.push("list".to_string());
-
-    let output = run_command("gluster", &args_list, true, false);
-    let status = output.status;
-
-    if !status.success() {
-        debug!(
-            "Volume quota list command failed with error: {}",
-            String::from_utf8_lossy(&output.stderr)
-        );
-        return Err(GlusterError::new(
-            String::from_utf8_lossy(&output.stderr).into_owned(),
-        ));
-    }
-    let output_str: String = String::from_utf8(output.stdout)?;
-    let quota_list = parse_quota_list(volume, &output_str);
-
-    Ok(quota_list)
+    GlusterClient::default().quota_list(volume)
 }
 
 #[test]
@@ -906,95 +1350,110 @@ fn parse_quota_list(volume: &str, output_str: &str) -> Vec<Quota> {
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_enable_bitrot(volume: &str) -> Result<i32, GlusterError> {
-    let arg_list: Vec<&str> = vec!["volume", "bitrot", volume, "enable"];
-    process_output(run_command("gluster", &arg_list, true, false))
+    GlusterClient::default().volume_enable_bitrot(volume)
 }
 
 /// Disable bitrot detection and remediation on the volume
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_disable_bitrot(volume: &str) -> Result<i32, GlusterError> {
-    let arg_list: Vec<&str> = vec!["volume", "bitrot", volume, "disable"];
-    process_output(run_command("gluster", &arg_list, true, false))
+    GlusterClient::default().volume_disable_bitrot(volume)
 }
 
 /// Set a bitrot option on the volume
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_set_bitrot_option(volume: &str, setting: &BitrotOption) -> Result<i32, GlusterError> {
-    let arg_list: Vec<String> = vec![
-        "volume".to_string(),
-        "bitrot".to_string(),
-        volume.to_string(),
-        setting.to_string(),
-        setting.value(),
-    ];
-    process_output(run_command("gluster", &arg_list, true, true))
+    GlusterClient::default().volume_set_bitrot_option(volume, setting)
 }
 
 /// Enable quotas on the volume
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_enable_quotas(volume: &str) -> Result<i32, GlusterError> {
-    let arg_list: Vec<&str> = vec!["volume", "quota", volume, "enable"];
-    process_output(run_command("gluster", &arg_list, true, false))
+    GlusterClient::default().volume_enable_quotas(volume)
 }
 
 /// Check if quotas are already enabled on a volume
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_quotas_enabled(volume: &str) -> Result<bool, GlusterError> {
-    let vol_info = volume_info(volume)?;
-    let quota = vol_info.options.get("features.quota");
-    match quota {
-        Some(v) => {
-            if v == "off" {
-                Ok(false)
-            } else if v == "on" {
-                Ok(true)
-            } else {
-                // No idea what this is
-                Ok(false)
-            }
-        }
-        None => Ok(false),
-    }
+    GlusterClient::default().volume_quotas_enabled(volume)
 }
 
 /// Disable quotas on the volume
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_disable_quotas(volume: &str) -> Result<i32, GlusterError> {
-    let arg_list: Vec<&str> = vec!["volume", "quota", volume, "disable"];
-    process_output(run_command("gluster", &arg_list, true, false))
+    GlusterClient::default().volume_disable_quotas(volume)
 }
 
 /// Removes a size quota to the volume and path.
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_remove_quota(volume: &str, path: &Path) -> Result<i32, GlusterError> {
-    let path_str = format!("{}", path.display());
-    let arg_list: Vec<&str> = vec!["volume", "quota", volume, "remove", &path_str];
-    process_output(run_command("gluster", &arg_list, true, false))
+    GlusterClient::default().volume_remove_quota(volume, path)
 }
 
 /// Adds a size quota to the volume and path.
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_add_quota(volume: &str, path: &Path, size: u64) -> Result<i32, GlusterError> {
-    let path_str = format!("{}", path.display());
-    let size_string = size.to_string();
-    let arg_list: Vec<&str> = vec![
-        "volume",
-        "quota",
-        volume,
-        "limit-usage",
-        &path_str,
-        &size_string,
-    ];
-
-    process_output(run_command("gluster", &arg_list, true, false))
+    GlusterClient::default().volume_add_quota(volume, path, size)
+}
+#[test]
+fn test_volume_remove_brick() {
+    use executor::CannedExecutor;
+    use std::sync::Arc;
+
+    let brick = Brick {
+        peer: Peer::new(
+            Uuid::parse_str("fc0924ad-62dd-4d63-8731-daad937a3006").unwrap(),
+            Host::new("10.0.0.5"),
+            State::PeerInCluster,
+        ),
+        path: PathBuf::from("/mnt/brick1"),
+        is_arbiter: false,
+    };
+    let canned = Arc::new(CannedExecutor::new());
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    client
+        .volume_remove_brick("test", vec![brick.clone()], false)
+        .unwrap();
+    let calls: Vec<Vec<String>> = canned
+        .calls()
+        .into_iter()
+        .map(|c| c[2..].to_vec())
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            vec!["vol", "status", "test"],
+            vec![
+                "volume",
+                "remove-brick",
+                "test",
+                "10.0.0.5:/mnt/brick1",
+                "start"
+            ],
+        ]
+    );
+
+    let canned = Arc::new(CannedExecutor::new().respond(
+        &["volume", "remove-brick"],
+        1,
+        "",
+        "volume remove-brick start: failed: Staging failed on 10.0.0.5",
+    ));
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    match client.volume_remove_brick("test", vec![brick], true) {
+        Err(GlusterError::CommandFailed { ref argv, .. }) => {
+            assert_eq!(argv.last().unwrap(), "force")
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
 }
+
 #[test]
 fn test_parse_volume_status() {
     let test_data = r#"
@@ -1002,7 +1461,14 @@ fn test_parse_volume_status() {
     ------------------------------------------------------------------------------
     Brick 172.31.46.33:/mnt/xvdf                49152     0          Y       14228
     Brick 172.31.19.130:/mnt/xvdf               49152     0          Y       14446
+    Brick ip-172-31-19-131.us-west-2.compute.in
+    ternal:/bricks/brick-1                      N/A       N/A        N       N/A
+    Brick 172.31.19.132:/mnt/xvdf               49153     0          Y       4194303
+    NFS Server on localhost                     2049      0          Y       14239
     Self-heal Daemon on localhost               N/A       N/A        Y       14248
+    Quota Daemon on localhost                   N/A       N/A        Y       14257
+    Bitrot Daemon on localhost                  N/A       N/A        Y       14266
+    Scrubber Daemon on localhost                N/A       N/A        N       N/A
     Self-heal Daemon on ip-172-31-19-130.us-wes
     t-2.compute.internal                        N/A       N/A        Y       14466
 
@@ -1013,41 +1479,97 @@ fn test_parse_volume_status() {
 "#;
     let result = parse_volume_status(test_data).unwrap();
     println!("status: {:?}", result);
-    // Have to inspect these manually because the UUID is randomly generated by the parser.
-    // It's either that or it has to be set to some fixed UUID.  Neither solution seems good
-    assert_eq!(result[0].brick.peer.hostname, "172.31.46.33".to_string());
-    assert_eq!(result[0].tcp_port, 49152);
-    assert_eq!(result[0].rdma_port, 0);
-    assert_eq!(result[0].online, true);
-    assert_eq!(result[0].pid, 14228);
-
-    assert_eq!(result[1].brick.peer.hostname, "172.31.19.130".to_string());
-    assert_eq!(result[1].tcp_port, 49152);
-    assert_eq!(result[1].rdma_port, 0);
-    assert_eq!(result[1].online, true);
-    assert_eq!(result[1].pid, 14446);
+    let bricks = &result.bricks;
+    assert_eq!(bricks.len(), 4);
+    assert_eq!(bricks[0].brick.peer.hostname, "172.31.46.33".to_string());
+    assert_eq!(bricks[0].tcp_port, 49152);
+    assert_eq!(bricks[0].rdma_port, 0);
+    assert_eq!(bricks[0].online, true);
+    assert_eq!(bricks[0].pid, 14228);
+
+    assert_eq!(bricks[1].brick.peer.hostname, "172.31.19.130".to_string());
+    assert_eq!(bricks[1].tcp_port, 49152);
+    assert_eq!(bricks[1].rdma_port, 0);
+    assert_eq!(bricks[1].online, true);
+    assert_eq!(bricks[1].pid, 14446);
+
+    assert_eq!(
+        bricks[2].brick.peer.hostname,
+        "ip-172-31-19-131.us-west-2.compute.internal"
+    );
+    assert_eq!(bricks[2].brick.path, PathBuf::from("/bricks/brick-1"));
+    assert_eq!(bricks[2].tcp_port, 0);
+    assert_eq!(bricks[2].online, false);
+    assert_eq!(bricks[2].pid, 0);
+    // pid_max can be up to 2^22
+    assert_eq!(bricks[3].pid, 4194303);
+
+    let daemons = &result.daemons;
+    assert_eq!(daemons.len(), 6);
+    assert_eq!(
+        daemons[0],
+        DaemonStatus {
+            name: "NFS Server".to_string(),
+            host: "localhost".to_string(),
+            online: true,
+            pid: 14239,
+        }
+    );
+    assert_eq!(daemons[4].name, "Scrubber Daemon");
+    assert!(!daemons[4].online);
+    assert_eq!(daemons[4].pid, 0);
+    // Names too long for the column are wrapped onto the next line
+    assert_eq!(daemons[5].name, "Self-heal Daemon");
+    assert_eq!(
+        daemons[5].host,
+        "ip-172-31-19-130.us-west-2.compute.internal"
+    );
+    assert_eq!(daemons[5].pid, 14466);
+    assert!(result.tasks.is_empty());
+
+    let test_data = r#"Status of volume: test
+Gluster process                             TCP Port  RDMA Port  Online  Pid
+------------------------------------------------------------------------------
+Brick 172.31.46.33:/mnt/xvdf                49152     0          Y       14228
+
+Task Status of Volume test
+------------------------------------------------------------------------------
+Task                 : Rebalance
+ID                   : 0e5b5b82-8ab1-4b4a-9e6f-9fa4b4c1a2c0
+Status               : completed
+
+Task                 : Remove brick
+ID                   : 2f5ad6c3-1d7e-4c0e-8a62-1b8b1e54cfd1
+Removed bricks:
+172.31.19.130:/mnt/xvdf
+Status               : in progress
+"#;
+    let result = parse_volume_status(test_data).unwrap();
+    assert_eq!(result.bricks.len(), 1);
+    assert!(result.daemons.is_empty());
+    assert_eq!(
+        result.tasks,
+        vec![
+            VolumeTask {
+                task_type: TaskType::Rebalance,
+                id: Uuid::parse_str("0e5b5b82-8ab1-4b4a-9e6f-9fa4b4c1a2c0").unwrap(),
+                status: "completed".to_string(),
+            },
+            VolumeTask {
+                task_type: TaskType::RemoveBrick,
+                id: Uuid::parse_str("2f5ad6c3-1d7e-4c0e-8a62-1b8b1e54cfd1").unwrap(),
+                status: "in progress".to_string(),
+            },
+        ]
+    );
 }
 
 /// Based on the replicas or erasure bits that are still available in the
 /// volume this will return
 /// True or False as to whether you can remove a Brick. This should be called
 /// before volume_remove_brick()
-pub fn ok_to_remove(volume: &str, _brick: &Brick) -> Result<bool, GlusterError> {
-    // TODO: switch over to native RPC call to eliminate String regex parsing
-    let arg_list: Vec<&str> = vec!["vol", "status", volume];
-
-    let output = run_command("gluster", &arg_list, true, false);
-    if !output.status.success() {
-        let stderr = String::from_utf8(output.stderr)?;
-        return Err(GlusterError::new(stderr));
-    }
-
-    let output_str = String::from_utf8(output.stdout)?;
-    let _bricks = parse_volume_status(&output_str)?;
-    // The redudancy requirement is needed here.  The code needs to understand what
-    // volume type
-    // it's operating on.
-    Ok(true)
+pub fn ok_to_remove(volume: &str, brick: &Brick) -> Result<bool, GlusterError> {
+    GlusterClient::default().ok_to_remove(volume, brick)
 }
 
 // pub fn volume_shrink_replicated(volume: &str,
@@ -1059,133 +1581,465 @@ pub fn ok_to_remove(volume: &str, _brick: &Brick) -> Result<bool, GlusterError>
 // ommit|force> - remove brick from volume <VOLNAME>
 // }
 //
-fn parse_volume_status(output_str: &str) -> Result<Vec<BrickStatus>, GlusterError> {
+/// Everything `gluster volume status <vol>` reports about a volume
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct VolumeStatus {
+    pub bricks: Vec<BrickStatus>,
+    /// The self-heal, NFS, quota, bitrot and scrubber daemons on each node
+    pub daemons: Vec<DaemonStatus>,
+    /// Rebalance and remove-brick operations on the volume
+    pub tasks: Vec<VolumeTask>,
+}
+
+/// A daemon gluster runs on a node to serve the volume
+#[derive(Debug, Eq, PartialEq)]
+pub struct DaemonStatus {
+    /// eg: Self-heal Daemon
+    pub name: String,
+    /// The node it runs on as glusterd names it.  The local node is localhost
+    pub host: String,
+    pub online: bool,
+    /// 0 if the daemon isn't running
+    pub pid: u32,
+}
+
+/// The kind of a long running volume operation
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TaskType {
+    Rebalance,
+    RemoveBrick,
+    Other(String),
+}
+
+impl TaskType {
+    fn new(name: &str) -> TaskType {
+        match name {
+            "Rebalance" => TaskType::Rebalance,
+            "Remove brick" => TaskType::RemoveBrick,
+            _ => TaskType::Other(name.to_string()),
+        }
+    }
+}
+
+/// A rebalance or remove-brick task listed by `gluster volume status`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VolumeTask {
+    pub task_type: TaskType,
+    pub id: Uuid,
+    /// eg: in progress, completed, failed
+    pub status: String,
+}
+
+fn parse_volume_status(output_str: &str) -> Result<VolumeStatus, GlusterError> {
     // Sample output
     // Status of volume: test
     // Gluster process                             TCP Port  RDMA Port  Online  Pid
     // ------------------------------------------------------------------------------
-    // Brick 192.168.1.6:/mnt/brick2               49154     0          Y
-    // 14940
-    // Brick 192.168.1.6:/mnt/brick3               49155     0          Y
-    // 14947
+    // Brick 192.168.1.6:/mnt/brick2               49154     0          Y       14940
+    // Self-heal Daemon on localhost               N/A       N/A        Y       14248
+    //
+    // Task Status of Volume test
+    // ------------------------------------------------------------------------------
+    // Task                 : Rebalance
+    // ID                   : 0e5b5b82-8ab1-4b4a-9e6f-9fa4b4c1a2c0
+    // Status               : completed
     //
-    let mut bricks: Vec<BrickStatus> = Vec::new();
+    let mut status = VolumeStatus::default();
+    // The CLI wraps process names that don't fit their column onto the next
+    // line
+    let mut process = String::new();
+    let mut in_tasks = false;
     for line in output_str.lines() {
-        // Skip the header crap
-        if line.starts_with("Status") {
+        let line = line.trim();
+        if in_tasks {
+            let mut parts = line.splitn(2, ':');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => (key.trim(), value.trim()),
+                _ => continue,
+            };
+            match key {
+                "Task" => status.tasks.push(VolumeTask {
+                    task_type: TaskType::new(value),
+                    id: Uuid::nil(),
+                    status: String::new(),
+                }),
+                "ID" => {
+                    if let Some(task) = status.tasks.last_mut() {
+                        task.id = Uuid::parse_str(value)?;
+                    }
+                }
+                "Status" => {
+                    if let Some(task) = status.tasks.last_mut() {
+                        task.status = value.to_string();
+                    }
+                }
+                _ => {}
+            }
             continue;
         }
-        if line.starts_with("Gluster") {
+        if line.starts_with("Task Status") {
+            in_tasks = true;
             continue;
         }
-        if line.starts_with('-') {
+        // Skip the header crap
+        if line.is_empty()
+            || line.starts_with("Status")
+            || line.starts_with("Gluster")
+            || line.starts_with('-')
+        {
             continue;
         }
-        let regex_str = r#"Brick\s+(?P<hostname>[a-zA-Z0-9.]+)
-:(?P<path>[/a-zA-z0-9]+)
-\s+(?P<tcp>[0-9]+)\s+(?P<rdma>[0-9]+)\s+(?P<online>[Y,N])\s+(?P<pid>[0-9]+)"#;
-        let brick_regex = Regex::new(&regex_str.replace("\n", ""))?;
-        if let Some(result) = brick_regex.captures(&line) {
-            let _tcp_port = match result.name("tcp") {
-                Some(port) => port,
-                None => {
-                    return Err(GlusterError::new(
-                        "Unable to find tcp port in gluster vol \
-                         status output"
-                            .to_string(),
-                    ));
-                }
-            };
-
-            let peer = Peer {
-                uuid: Uuid::new_v4(),
-                hostname: result.name("hostname").unwrap().as_str().to_string(),
-                status: State::Unknown,
-            };
-
-            let brick = Brick {
-                peer,
-                path: PathBuf::from(result.name("path").unwrap().as_str()),
-            };
-
-            let online = match result.name("online").unwrap().as_str() {
-                "Y" => true,
-                "N" => false,
-                _ => false,
-            };
+        // Every complete row ends with port, port, online and pid columns
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        let n = columns.len();
+        if n < 5 || (columns[n - 2] != "Y" && columns[n - 2] != "N") {
+            process.push_str(line);
+            continue;
+        }
+        process.push_str(&columns[..n - 4].join(" "));
+        let name = ::std::mem::take(&mut process);
+        // Ports and pids are N/A for anything that isn't running
+        let port = |column: &str| -> Result<u16, GlusterError> {
+            match column {
+                "N/A" => Ok(0),
+                column => Ok(u16::from_str(column)?),
+            }
+        };
+        let online = columns[n - 2] == "Y";
+        let pid = match columns[n - 1] {
+            "N/A" => 0,
+            pid => u32::from_str(pid)?,
+        };
 
-            let status = BrickStatus {
-                brick,
-                tcp_port: u16::from_str(result.name("tcp").unwrap().as_str())?,
-                rdma_port: u16::from_str(result.name("rdma").unwrap().as_str())?,
+        if let Some(brick) = name.strip_prefix("Brick ") {
+            status.bricks.push(BrickStatus {
+                brick: Brick::from_str(brick)?,
+                tcp_port: port(columns[n - 4])?,
+                rdma_port: port(columns[n - 3])?,
                 online,
-                pid: u16::from_str(result.name("pid").unwrap().as_str())?,
-            };
-            bricks.push(status);
+                pid,
+            });
+        } else if let Some(i) = name.rfind(" on ") {
+            status.daemons.push(DaemonStatus {
+                name: name[..i].to_string(),
+                host: name[i + 4..].to_string(),
+                online,
+                pid,
+            });
         }
     }
-    Ok(bricks)
+    Ok(status)
 }
 
 /// Query the status of the volume given.
 /// # Failures
 /// Will return GlusterError if the command fails to run
-pub fn volume_status(volume: &str) -> Result<Vec<BrickStatus>, GlusterError> {
-    let arg_list: Vec<&str> = vec!["vol", "status", volume];
+pub fn volume_status(volume: &str) -> Result<VolumeStatus, GlusterError> {
+    GlusterClient::default().volume_status(volume)
+}
 
-    let output = run_command("gluster", &arg_list, true, false);
-    if !output.status.success() {
-        let stderr = String::from_utf8(output.stderr)?;
-        return Err(GlusterError::new(stderr));
-    }
+/// Disk usage and filesystem details of a brick as reported by
+/// `gluster volume status <vol> detail`.  glusterd can only report the
+/// details of online bricks so the numbers are 0 and the strings empty for a
+/// brick that is offline.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BrickDetail {
+    pub brick: Brick,
+    pub online: bool,
+    /// Size of the brick's filesystem in bytes
+    pub total_bytes: u64,
+    /// Free bytes on the brick's filesystem.  glusterd counts the blocks
+    /// reserved for root as free, so unprivileged writes can hit ENOSPC
+    /// before this reaches 0.
+    pub free_bytes: u64,
+    pub total_inodes: u64,
+    pub free_inodes: u64,
+    /// The block device backing the brick, eg: /dev/sdb
+    pub device: String,
+    /// eg: xfs
+    pub fs_type: String,
+    /// Mount options of the brick's filesystem, comma separated
+    pub mount_options: String,
+    pub block_size: u64,
+}
 
-    let output_str = String::from_utf8(output.stdout)?;
-    let bricks = parse_volume_status(&output_str)?;
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct VolumeStatusCliXml {
+    #[serde(rename = "opRet")]
+    pub ret: i32,
+    #[serde(rename = "opErrno")]
+    pub errno: i32,
+    #[serde(rename = "opErrstr")]
+    pub err_str: Option<String>,
+    #[serde(rename = "volStatus", default)]
+    pub vol_status: XmlVolStatus,
+}
 
-    Ok(bricks)
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct XmlVolStatus {
+    #[serde(default)]
+    pub volumes: XmlStatusVolumes,
 }
-// pub fn volume_shrink_replicated(volume: &str,
-// replica_count: usize,
-// bricks: Vec<Brick>,
-// force: bool) -> Result<i32,String> {
-// volume remove-brick <VOLNAME> [replica <COUNT>] <BRICK> ...
-// <start|stop|status|c
-// ommit|force> - remove brick from volume <VOLNAME>
-// }
-//
 
-/// This will remove a brick from the volume
-/// # Failures
-/// Will return GlusterError if the command fails to run
-pub fn volume_remove_brick(
-    volume: &str,
-    bricks: Vec<Brick>,
-    force: bool,
-) -> Result<i32, GlusterError> {
-    if bricks.is_empty() {
-        return Err(GlusterError::new(
-            "The brick list is empty. Not shrinking volume".to_string(),
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct XmlStatusVolumes {
+    #[serde(default)]
+    pub volume: Vec<VolumeStatusXml>,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct VolumeStatusXml {
+    #[serde(rename = "volName")]
+    pub name: String,
+    #[serde(default)]
+    pub node: Vec<NodeXml>,
+}
+
+/// A brick or daemon in `gluster volume status --xml` output.  The detail
+/// fields are only filled in by `volume status <vol> detail` for online
+/// bricks.
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct NodeXml {
+    pub hostname: String,
+    pub path: String,
+    pub peerid: Uuid,
+    pub status: String,
+    #[serde(default)]
+    pub pid: String,
+    #[serde(rename = "sizeTotal", default)]
+    pub size_total: String,
+    #[serde(rename = "sizeFree", default)]
+    pub size_free: String,
+    #[serde(default)]
+    pub device: String,
+    #[serde(rename = "blockSize", default)]
+    pub block_size: String,
+    #[serde(rename = "mntOptions", default)]
+    pub mount_options: String,
+    #[serde(rename = "fsName", default)]
+    pub fs_name: String,
+    #[serde(rename = "inodesTotal", default)]
+    pub inodes_total: String,
+    #[serde(rename = "inodesFree", default)]
+    pub inodes_free: String,
+}
+
+impl NodeXml {
+    fn brick(&self) -> Brick {
+        Brick {
+            peer: Peer::new(
+                self.peerid,
+                Host::new(self.hostname.clone()),
+                State::Unknown,
+            ),
+            path: PathBuf::from(&self.path),
+            is_arbiter: false,
+        }
+    }
+
+    fn into_brick_detail(self) -> Result<BrickDetail, GlusterError> {
+        // Offline bricks have no details at all
+        let number = |value: &str| -> Result<u64, GlusterError> {
+            match value.trim() {
+                "" => Ok(0),
+                value => Ok(u64::from_str(value)?),
+            }
+        };
+        Ok(BrickDetail {
+            brick: self.brick(),
+            online: self.status.trim() == "1",
+            total_bytes: number(&self.size_total)?,
+            free_bytes: number(&self.size_free)?,
+            total_inodes: number(&self.inodes_total)?,
+            free_inodes: number(&self.inodes_free)?,
+            device: self.device.trim().to_string(),
+            fs_type: self.fs_name.trim().to_string(),
+            mount_options: self.mount_options.trim().to_string(),
+            block_size: number(&self.block_size)?,
+        })
+    }
+}
+
+/// Parse the output of `gluster volume status --xml`
+fn parse_volume_status_xml(
+    output_str: &str,
+    argv: &[String],
+) -> Result<Vec<VolumeStatusXml>, GlusterError> {
+    let cli_output: VolumeStatusCliXml = serde_xml_rs::from_str(output_str)?;
+    if cli_output.ret != 0 {
+        return Err(xml_op_error(
+            argv,
+            cli_output.ret,
+            cli_output.errno,
+            cli_output.err_str,
         ));
     }
+    Ok(cli_output.vol_status.volumes.volume)
+}
 
-    for brick in bricks {
-        let ok = ok_to_remove(&volume, &brick)?;
-        if ok {
-            let mut arg_list: Vec<&str> = vec!["volume", "remove-brick", volume];
+/// Where the effective value of a volume option comes from
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OptionSource {
+    /// Nobody set it.  This is Gluster's default
+    Default,
+    /// Set with `gluster volume set`.  Listed under "Options Reconfigured"
+    Reconfigured,
+}
 
-            if force {
-                arg_list.push("force");
-            }
-            arg_list.push("start");
+/// A volume option with the value the volume is actually using
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VolumeOption {
+    pub key: String,
+    pub value: String,
+    pub source: OptionSource,
+}
 
-            let _status = process_output(run_command("gluster", &arg_list, true, true));
-        } else {
-            return Err(GlusterError::new(
-                "Unable to remove brick due to redundancy failure".to_string(),
-            ));
-        }
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct VolumeGetCliXml {
+    #[serde(rename = "opRet")]
+    pub ret: i32,
+    #[serde(rename = "opErrno")]
+    pub errno: i32,
+    #[serde(rename = "opErrstr")]
+    pub err_str: Option<String>,
+    #[serde(rename = "volGetopts", default)]
+    pub vol_getopts: XmlVolGetopts,
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct XmlVolGetopts {
+    #[serde(rename = "Opt", default)]
+    pub opt: Vec<VolGetOptXml>,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct VolGetOptXml {
+    #[serde(rename = "Option")]
+    pub option: String,
+    #[serde(rename = "Value", default)]
+    pub value: String,
+}
+
+/// Parse the output of `gluster volume get <volume> <key|all> --xml` into
+/// (key, value) pairs in the order they were listed
+fn parse_volume_get_xml(
+    output_str: &str,
+    argv: &[String],
+) -> Result<Vec<(String, String)>, GlusterError> {
+    let cli_output: VolumeGetCliXml = serde_xml_rs::from_str(output_str)?;
+    if cli_output.ret != 0 {
+        return Err(xml_op_error(
+            argv,
+            cli_output.ret,
+            cli_output.errno,
+            cli_output.err_str,
+        ));
     }
-    Ok(0)
+    Ok(cli_output
+        .vol_getopts
+        .opt
+        .into_iter()
+        .map(|o| (o.option.trim().to_string(), o.value.trim().to_string()))
+        .collect())
+}
+
+// Newer releases mark values nobody set with " (DEFAULT)".  Older ones
+// don't so anything not reconfigured is taken to be a default.
+fn volume_option(key: String, value: String, reconfigured: &[String]) -> VolumeOption {
+    let (value, source) = match value.strip_suffix("(DEFAULT)") {
+        Some(value) => (value.trim_end().to_string(), OptionSource::Default),
+        None if reconfigured.contains(&key) => (value, OptionSource::Reconfigured),
+        None => (value, OptionSource::Default),
+    };
+    VolumeOption { key, value, source }
+}
+
+/// Query the disk usage and filesystem details of every brick in the volume
+/// # Failures
+/// Will return GlusterError if the command fails to run or the volume
+/// doesn't exist
+pub fn volume_status_detail(volume: &str) -> Result<Vec<BrickDetail>, GlusterError> {
+    GlusterClient::default().volume_status_detail(volume)
+}
+
+#[test]
+fn test_volume_status_detail() {
+    use executor::CannedExecutor;
+    use std::fs::File;
+    use std::io::Read;
+    use std::sync::Arc;
+
+    let test_data = {
+        let mut f = File::open("tests/volume_status_detail.xml").unwrap();
+        let mut s = String::new();
+        f.read_to_string(&mut s).unwrap();
+        s
+    };
+    let canned = Arc::new(CannedExecutor::new().respond(
+        &["volume", "status", "test", "detail"],
+        0,
+        &test_data,
+        "",
+    ));
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    let details = client.volume_status_detail("test").unwrap();
+    assert_eq!(
+        canned.calls()[0][2..],
+        ["volume", "status", "test", "detail", "--xml"]
+    );
+    assert_eq!(details.len(), 3);
+
+    assert_eq!(details[0].brick.peer.hostname, "172.31.46.33");
+    assert_eq!(
+        details[0].brick.peer.uuid,
+        Uuid::parse_str("78f68270-201a-4d8a-bad3-7cded6e6b7d8").unwrap()
+    );
+    assert_eq!(details[0].brick.path, PathBuf::from("/mnt/xvdf"));
+    assert!(details[0].online);
+    assert_eq!(details[0].total_bytes, 10725883904);
+    assert_eq!(details[0].free_bytes, 9652142080);
+    assert_eq!(details[0].total_inodes, 5242368);
+    assert_eq!(details[0].free_inodes, 5231004);
+    assert_eq!(details[0].device, "/dev/xvdf");
+    assert_eq!(details[0].fs_type, "xfs");
+    assert_eq!(details[0].mount_options, "rw,noatime,attr2,inode64,noquota");
+    assert_eq!(details[0].block_size, 4096);
+    assert_eq!(details[1].fs_type, "ext4");
+
+    // Offline bricks are listed without details
+    assert!(!details[2].online);
+    assert_eq!(details[2].total_bytes, 0);
+    assert_eq!(details[2].device, "");
+
+    let missing = "<cliOutput><opRet>-1</opRet><opErrno>0</opErrno>\
+                   <opErrstr>Volume missing does not exist</opErrstr></cliOutput>";
+    let canned =
+        Arc::new(CannedExecutor::new().respond(&["volume", "status", "missing"], 0, missing, ""));
+    let client = GlusterClient::builder().executor(canned).build();
+    match client.volume_status_detail("missing") {
+        Err(GlusterError::VolumeNotFound(ref volume)) => assert_eq!(volume, "missing"),
+        other => panic!("expected VolumeNotFound, got {:?}", other),
+    }
+}
+// pub fn volume_shrink_replicated(volume: &str,
+// replica_count: usize,
+// bricks: Vec<Brick>,
+// force: bool) -> Result<i32,String> {
+// volume remove-brick <VOLNAME> [replica <COUNT>] <BRICK> ...
+// <start|stop|status|c
+// ommit|force> - remove brick from volume <VOLNAME>
+// }
+//
+
+/// This will remove a brick from the volume
+/// # Failures
+/// Will return GlusterError if the command fails to run
+pub fn volume_remove_brick(
+    volume: &str,
+    bricks: Vec<Brick>,
+    force: bool,
+) -> Result<i32, GlusterError> {
+    GlusterClient::default().volume_remove_brick(volume, bricks, force)
 }
 
 // volume add-brick <VOLNAME> [<stripe|replica> <COUNT>]
@@ -1194,58 +2048,79 @@ pub fn volume_remove_brick(
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_add_brick(volume: &str, bricks: &[Brick], force: bool) -> Result<i32, GlusterError> {
-    if bricks.is_empty() {
-        return Err(GlusterError::new(
-            "The brick list is empty. Not expanding volume".to_string(),
-        ));
-    }
-
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("volume".to_string());
-    arg_list.push("add-brick".to_string());
-    arg_list.push(volume.to_string());
-
-    for brick in bricks.iter() {
-        arg_list.push(brick.to_string());
-    }
-    if force {
-        arg_list.push("force".to_string());
-    }
-    process_output(run_command("gluster", &arg_list, true, true))
+    GlusterClient::default().volume_add_brick(volume, bricks, force)
 }
 
 /// Once a volume is created it needs to be started.  This starts the volume
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_start(volume: &str, force: bool) -> Result<i32, GlusterError> {
-    // Should I check the volume exists first?
-    let mut arg_list: Vec<&str> = vec!["volume", "start", volume];
+    GlusterClient::default().volume_start(volume, force)
+}
 
-    if force {
-        arg_list.push("force");
+#[test]
+fn test_volume_command_errors() {
+    use executor::CannedExecutor;
+    use std::error::Error;
+    use std::sync::Arc;
+
+    let canned = CannedExecutor::new()
+        .respond(
+            &["volume", "info", "missing"],
+            1,
+            "",
+            "Volume missing does not exist\n",
+        )
+        .respond(
+            &["volume", "start", "test"],
+            1,
+            "",
+            "volume start: test: failed: Volume test already started\n",
+        );
+    let client = GlusterClient::builder().executor(Arc::new(canned)).build();
+
+    match client.volume_info("missing") {
+        Err(GlusterError::VolumeNotFound(ref volume)) => assert_eq!(volume, "missing"),
+        other => panic!("expected VolumeNotFound, got {:?}", other),
+    }
+
+    match client.volume_start("test", false) {
+        Err(GlusterError::CommandFailed {
+            argv,
+            exit_code,
+            stderr,
+            kind,
+        }) => {
+            assert_eq!(
+                argv,
+                vec!["gluster", "--mode=script", "volume", "start", "test"]
+            );
+            assert_eq!(exit_code, Some(1));
+            assert!(stderr.contains("already started"));
+            assert_eq!(kind, CliFailure::Unknown);
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
     }
-    process_output(run_command("gluster", &arg_list, true, true))
+
+    let err = GlusterError::from(u16::from_str("70000").unwrap_err());
+    assert!(err.source().is_some());
+    assert!(GlusterError::VolumeNotFound("test".to_string())
+        .source()
+        .is_none());
 }
 
 /// This stops a running volume
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_stop(volume: &str, force: bool) -> Result<i32, GlusterError> {
-    let mut arg_list: Vec<&str> = vec!["volume", "stop", volume];
-
-    if force {
-        arg_list.push("force");
-    }
-    process_output(run_command("gluster", &arg_list, true, true))
+    GlusterClient::default().volume_stop(volume, force)
 }
 
 /// This deletes a stopped volume
 /// # Failures
 /// Will return GlusterError if the command fails to run
 pub fn volume_delete(volume: &str) -> Result<i32, GlusterError> {
-    let arg_list: Vec<&str> = vec!["volume", "delete", volume];
-
-    process_output(run_command("gluster", &arg_list, true, true))
+    GlusterClient::default().volume_delete(volume)
 }
 
 /// This function doesn't do anything yet.  It is a place holder because
@@ -1257,81 +2132,383 @@ pub fn volume_rebalance(_volume: &str) {
     // [force]|stop|status}}
 }
 
-fn volume_create<T: ToString>(
-    volume: &str,
-    options: &HashMap<VolumeTranslator, T>,
-    transport: &Transport,
-    bricks: &[Brick],
-    force: bool,
-) -> Result<i32, GlusterError> {
-    if bricks.is_empty() {
-        return Err(GlusterError::new(
-            "The brick list is empty. Not creating volume".to_string(),
-        ));
-    }
+/// Set an option on the volume.  Every value is validated before any of
+/// them is set.  Setting stops at the first option that fails.
+/// # Failures
+/// Will return GlusterError::InvalidArgument if a value is invalid or
+/// the error of the first option that couldn't be set
+pub fn volume_set_options(volume: &str, settings: &[GlusterOption]) -> Result<i32, GlusterError> {
+    GlusterClient::default().volume_set_options(volume, settings)
+}
 
-    // TODO: figure out how to check each VolumeTranslator type
-    // if (bricks.len() % replica_count) != 0 {
-    // return Err("The brick list and replica count are not multiples. Not creating
-    // volume".to_string());
-    // }
-    //
+#[test]
+fn test_volume_set_options_validates() {
+    use executor::CannedExecutor;
+    use std::sync::Arc;
+
+    let canned = Arc::new(CannedExecutor::new());
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    let settings = vec![
+        GlusterOption::NetworkFrameTimeout(30),
+        GlusterOption::ClientGraceTimeout(5),
+    ];
+    match client.volume_set_options("test", &settings) {
+        Err(GlusterError::InvalidArgument(ref msg)) => {
+            assert!(msg.contains("client.grace-timeout"))
+        }
+        other => panic!("expected InvalidArgument, got {:?}", other),
+    }
+    // Not even the valid option was set
+    assert!(canned.calls().is_empty());
+
+    client.volume_set_options("test", &settings[..1]).unwrap();
+    assert_eq!(
+        canned.calls(),
+        vec![vec![
+            "gluster",
+            "--mode=script",
+            "volume",
+            "set",
+            "test",
+            "network.frame-timeout",
+            "30",
+        ]]
+    );
+    // The first failure is returned as it is and nothing after it is set
+    let canned = Arc::new(CannedExecutor::new().respond(
+        &["volume", "set", "test", "network.frame-timeout"],
+        1,
+        "",
+        "volume set: failed: Another transaction is in progress for test.",
+    ));
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    let settings = vec![
+        GlusterOption::NetworkFrameTimeout(30),
+        GlusterOption::ClientGraceTimeout(60),
+    ];
+    match client.volume_set_options("test", &settings) {
+        Err(GlusterError::CommandFailed { ref kind, .. }) => {
+            assert_eq!(*kind, CliFailure::TransactionInProgress)
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
+    assert_eq!(canned.calls().len(), 1);
+}
 
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("volume".to_string());
-    arg_list.push("create".to_string());
-    arg_list.push(volume.to_string());
+/// How volume_apply_options undoes an option it set
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Restore {
+    /// Set it back to the value it had before
+    Set(GlusterOption),
+    /// Reset it, because it had Gluster's default before
+    Reset(String),
+}
 
-    for (key, value) in options.iter() {
-        arg_list.push(key.clone().to_string());
-        arg_list.push(value.to_string());
+impl Restore {
+    /// The key of the option being restored
+    pub fn key(&self) -> String {
+        match *self {
+            Restore::Set(ref option) => option.key(),
+            Restore::Reset(ref key) => key.clone(),
+        }
     }
+}
 
-    arg_list.push("transport".to_string());
-    arg_list.push(transport.clone().to_string());
+/// What volume_apply_options did.  Check is_success(): a failed apply is
+/// still returned as Ok.
+#[derive(Debug)]
+#[must_use = "the options may not have been applied, check is_success()"]
+pub struct ApplyReport {
+    /// The options that were set, in the order they were set
+    pub applied: Vec<GlusterOption>,
+    /// The option that couldn't be set and why.  None if all of them were.
+    pub failed: Option<(GlusterOption, GlusterError)>,
+    /// How the options were undone after the failure, last set first.  This
+    /// includes the failed option unless glusterd rejected it before
+    /// committing anything.
+    pub rolled_back: Vec<Restore>,
+    /// The undoing that failed.  These options keep the value from settings.
+    pub rollback_failed: Vec<(Restore, GlusterError)>,
+}
 
-    for brick in bricks.iter() {
-        arg_list.push(brick.to_string());
+impl ApplyReport {
+    /// Whether every option was set
+    pub fn is_success(&self) -> bool {
+        self.failed.is_none()
     }
-    if force {
-        arg_list.push("force".to_string());
+}
+
+// Whether glusterd turned the command down before committing anything
+fn is_rejected(e: &GlusterError) -> bool {
+    match *e {
+        GlusterError::CommandFailed { ref kind, .. } => matches!(
+            *kind,
+            CliFailure::StagingFailed { .. }
+                | CliFailure::LockingFailed { .. }
+                | CliFailure::TransactionInProgress
+        ),
+        _ => false,
     }
-    process_output(run_command("gluster", &arg_list, true, true))
 }
 
-fn vol_set(volume: &str, option: &GlusterOption) -> Result<i32, GlusterError> {
-    let mut arg_list: Vec<String> = Vec::new();
-    arg_list.push("volume".to_string());
-    arg_list.push("set".to_string());
-    arg_list.push(volume.to_string());
+/// Set several options on the volume so that either all of them are set or,
+/// as far as possible, none are.  See GlusterClient::volume_apply_options.
+///
+/// **A failed apply is not an error.**  It returns Ok with a report whose
+/// is_success() is false, after rolling back what it could.
+/// # Failures
+/// Will return GlusterError::InvalidArgument if a value is invalid or
+/// GlusterError if the current values can't be read.  Nothing has been set
+/// in either case.
+pub fn volume_apply_options(
+    volume: &str,
+    settings: &[GlusterOption],
+) -> Result<ApplyReport, GlusterError> {
+    GlusterClient::default().volume_apply_options(volume, settings)
+}
+
+#[test]
+fn test_volume_apply_options() {
+    use super::Toggle;
+    use executor::CannedExecutor;
+    use std::fs::File;
+    use std::io::Read;
+    use std::sync::Arc;
+
+    let volume_info = {
+        let mut f = File::open("tests/volume_info.xml").unwrap();
+        let mut s = String::new();
+        f.read_to_string(&mut s).unwrap();
+        s
+    };
+    let get = |key: &str, value: &str| {
+        format!(
+            "<cliOutput><opRet>0</opRet><opErrno>0</opErrno><opErrstr/><volGetopts>\
+             <count>1</count><Opt><Option>{}</Option><Value>{}</Value></Opt>\
+             </volGetopts></cliOutput>",
+            key, value
+        )
+    };
+    let canned = |set_error: &str| {
+        Arc::new(
+            CannedExecutor::new()
+                .respond(&["volume", "info", "gv0"], 0, &volume_info, "")
+                .respond(
+                    &["volume", "get", "gv0", "readdir-ahead"],
+                    0,
+                    &get("performance.readdir-ahead", "on"),
+                    "",
+                )
+                .respond(
+                    &["volume", "get", "gv0", "cluster.self-heal-daemon"],
+                    0,
+                    &get("cluster.self-heal-daemon", "on (DEFAULT)"),
+                    "",
+                )
+                .respond(
+                    &["volume", "get", "gv0", "network.frame-timeout"],
+                    0,
+                    &get("network.frame-timeout", "1800"),
+                    "",
+                )
+                .respond(
+                    &["volume", "get", "gv0", "nfs.disable"],
+                    0,
+                    &get("nfs.disable", "on"),
+                    "",
+                )
+                .respond(
+                    &["volume", "set", "gv0", "network.frame-timeout"],
+                    1,
+                    "",
+                    set_error,
+                ),
+        )
+    };
+    let settings = vec![
+        // A short key is restored by its full key
+        GlusterOption::Unknown("readdir-ahead".to_string(), "off".to_string()),
+        GlusterOption::ClusterSelfHealDaemon(Toggle::Off),
+        GlusterOption::NetworkFrameTimeout(60),
+        GlusterOption::NfsDisable(Toggle::Off),
+    ];
+
+    // Rejected while staging so only the options before it are undone
+    let executor = canned("volume set: failed: Staging failed on server2");
+    let client = GlusterClient::builder().executor(executor.clone()).build();
+    let report = client.volume_apply_options("gv0", &settings).unwrap();
+    assert!(!report.is_success());
+    assert_eq!(report.applied, settings[..2].to_vec());
+    assert_eq!(
+        report.failed.as_ref().map(|f| &f.0),
+        Some(&GlusterOption::NetworkFrameTimeout(60))
+    );
+    assert_eq!(
+        report.rolled_back,
+        vec![
+            Restore::Reset("cluster.self-heal-daemon".to_string()),
+            Restore::Set(GlusterOption::PerformanceReadDirAhead(Toggle::On)),
+        ]
+    );
+    assert!(report.rollback_failed.is_empty());
+    let calls: Vec<Vec<String>> = executor
+        .calls()
+        .into_iter()
+        .map(|c| c[2..].to_vec())
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            vec!["volume", "info", "gv0", "--xml"],
+            vec!["volume", "get", "gv0", "readdir-ahead", "--xml"],
+            vec!["volume", "get", "gv0", "cluster.self-heal-daemon", "--xml"],
+            vec!["volume", "get", "gv0", "network.frame-timeout", "--xml"],
+            vec!["volume", "get", "gv0", "nfs.disable", "--xml"],
+            vec!["volume", "set", "gv0", "readdir-ahead", "off"],
+            vec!["volume", "set", "gv0", "cluster.self-heal-daemon", "off"],
+            vec!["volume", "set", "gv0", "network.frame-timeout", "60"],
+            vec!["volume", "reset", "gv0", "cluster.self-heal-daemon"],
+            vec!["volume", "set", "gv0", "performance.readdir-ahead", "on"],
+        ]
+    );
+
+    // A failed commit may have set it on some peers so it is undone too
+    let executor = canned("volume set: failed: Commit failed on server2");
+    let client = GlusterClient::builder().executor(executor.clone()).build();
+    let report = client.volume_apply_options("gv0", &settings[1..3]).unwrap();
+    assert_eq!(
+        report.rolled_back,
+        vec![
+            Restore::Reset("network.frame-timeout".to_string()),
+            Restore::Reset("cluster.self-heal-daemon".to_string()),
+        ]
+    );
+
+    let report = client.volume_apply_options("gv0", &settings[..2]).unwrap();
+    assert!(report.is_success());
+    assert_eq!(report.applied.len(), 2);
+    assert!(report.rolled_back.is_empty());
+}
 
-    arg_list.push(option.to_string());
-    arg_list.push(option.value());
+/// Every option the volume has with the value in effect, defaults included
+/// # Failures
+/// Will return GlusterError if the command fails to run or the volume
+/// doesn't exist
+pub fn volume_get_options(volume: &str) -> Result<Vec<VolumeOption>, GlusterError> {
+    GlusterClient::default().volume_get_options(volume)
+}
 
-    process_output(run_command("gluster", &arg_list, true, true))
+/// The value in effect for one volume option
+/// # Failures
+/// Will return GlusterError if the command fails to run, the volume
+/// doesn't exist or there is no such option
+pub fn volume_get_option(volume: &str, key: &str) -> Result<VolumeOption, GlusterError> {
+    GlusterClient::default().volume_get_option(volume, key)
 }
 
-/// Set an option on the volume
+/// Put a volume option back to its default.  key "all" resets every option.
 /// # Failures
 /// Will return GlusterError if the command fails to run
-pub fn volume_set_options(volume: &str, settings: &[GlusterOption]) -> Result<i32, GlusterError> {
-    let results: Vec<Result<i32, GlusterError>> = settings
-        .iter()
-        .map(|gluster_opt| vol_set(volume, gluster_opt))
-        .collect();
+pub fn volume_reset_option(volume: &str, key: &str, force: bool) -> Result<(), GlusterError> {
+    GlusterClient::default().volume_reset_option(volume, key, force)
+}
+
+#[test]
+fn test_volume_get_options() {
+    use executor::CannedExecutor;
+    use std::fs::File;
+    use std::io::Read;
+    use std::sync::Arc;
 
-    let mut error_list: Vec<String> = Vec::new();
-    for result in results {
-        match result {
-            Ok(_) => {}
-            Err(e) => error_list.push(e.to_string()),
+    let volume_info = {
+        let mut f = File::open("tests/volume_info.xml").unwrap();
+        let mut s = String::new();
+        f.read_to_string(&mut s).unwrap();
+        s
+    };
+    let canned = Arc::new(
+        CannedExecutor::new()
+            .respond(&["volume", "info", "gv0"], 0, &volume_info, "")
+            .respond(
+                &["volume", "get", "gv0", "all"],
+                0,
+                "<cliOutput><opRet>0</opRet><opErrno>0</opErrno><opErrstr/><volGetopts>\
+                 <count>3</count>\
+                 <Opt><Option>cluster.lookup-unhashed</Option><Value>on</Value></Opt>\
+                 <Opt><Option>performance.readdir-ahead</Option><Value>on</Value></Opt>\
+                 <Opt><Option>cluster.self-heal-daemon</Option><Value>on (DEFAULT)</Value></Opt>\
+                 </volGetopts></cliOutput>",
+                "",
+            )
+            .respond(
+                &["volume", "get", "gv0", "readdir-ahead"],
+                0,
+                "<cliOutput><opRet>0</opRet><opErrno>0</opErrno><opErrstr/><volGetopts>\
+                 <count>1</count>\
+                 <Opt><Option>performance.readdir-ahead</Option><Value>on</Value></Opt>\
+                 </volGetopts></cliOutput>",
+                "",
+            )
+            .respond(
+                &["volume", "get", "missing"],
+                0,
+                "<cliOutput><opRet>-1</opRet><opErrno>0</opErrno>\
+                 <opErrstr>Volume missing does not exist</opErrstr></cliOutput>",
+                "",
+            ),
+    );
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+
+    let options = client.volume_get_options("gv0").unwrap();
+    assert_eq!(options.len(), 3);
+    assert_eq!(
+        options[0],
+        VolumeOption {
+            key: "cluster.lookup-unhashed".to_string(),
+            value: "on".to_string(),
+            source: OptionSource::Default,
         }
-    }
-    if !error_list.is_empty() {
-        return Err(GlusterError::new(error_list.join("\n")));
+    );
+    assert_eq!(options[1].source, OptionSource::Reconfigured);
+    assert_eq!(options[2].value, "on");
+    assert_eq!(options[2].source, OptionSource::Default);
+
+    let option = client.volume_get_option("gv0", "readdir-ahead").unwrap();
+    assert_eq!(option.key, "performance.readdir-ahead");
+    assert_eq!(option.source, OptionSource::Reconfigured);
+
+    match client.volume_get_options("missing") {
+        Err(GlusterError::VolumeNotFound(ref volume)) => assert_eq!(volume, "missing"),
+        other => panic!("expected VolumeNotFound, got {:?}", other),
     }
 
-    Ok(0)
+    client
+        .volume_reset_option("gv0", "nfs.disable", false)
+        .unwrap();
+    client.volume_reset_option("gv0", "all", true).unwrap();
+    let calls = canned.calls();
+    assert_eq!(
+        calls[calls.len() - 2..].to_vec(),
+        vec![
+            vec![
+                "gluster",
+                "--mode=script",
+                "volume",
+                "reset",
+                "gv0",
+                "nfs.disable"
+            ],
+            vec![
+                "gluster",
+                "--mode=script",
+                "volume",
+                "reset",
+                "gv0",
+                "all",
+                "force"
+            ],
+        ]
+    );
 }
 
 /// This creates a new replicated volume
@@ -1344,17 +2521,21 @@ pub fn volume_create_replicated(
     bricks: &[Brick],
     force: bool,
 ) -> Result<i32, GlusterError> {
-    let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
-    volume_translators.insert(VolumeTranslator::Replica, replica_count);
-
-    volume_create(volume, &volume_translators, &transport, &bricks, force)
+    GlusterClient::default().volume_create_replicated(
+        volume,
+        replica_count,
+        transport,
+        bricks,
+        force,
+    )
 }
 
 /// The arbiter volume is special subset of replica volumes that is aimed at preventing
 /// split-brains and providing the same consistency guarantees as a normal replica 3 volume
 /// without consuming 3x space.
 /// # Failures
-/// Will return GlusterError if the command fails to run
+/// Will return GlusterError::Unsupported before Gluster 3.7 or
+/// GlusterError if the command fails to run
 pub fn volume_create_arbiter(
     volume: &str,
     replica_count: usize,
@@ -1363,16 +2544,20 @@ pub fn volume_create_arbiter(
     bricks: &[Brick],
     force: bool,
 ) -> Result<i32, GlusterError> {
-    let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
-    volume_translators.insert(VolumeTranslator::Replica, replica_count);
-    volume_translators.insert(VolumeTranslator::Arbiter, arbiter_count);
-
-    volume_create(volume, &volume_translators, &transport, &bricks, force)
+    GlusterClient::default().volume_create_arbiter(
+        volume,
+        replica_count,
+        arbiter_count,
+        transport,
+        bricks,
+        force,
+    )
 }
 
 /// This creates a new striped volume
 /// # Failures
-/// Will return GlusterError if the command fails to run
+/// Will return GlusterError::Unsupported on Gluster 6.0 and later or
+/// GlusterError if the command fails to run
 pub fn volume_create_striped(
     volume: &str,
     stripe: usize,
@@ -1380,15 +2565,13 @@ pub fn volume_create_striped(
     bricks: &[Brick],
     force: bool,
 ) -> Result<i32, GlusterError> {
-    let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
-    volume_translators.insert(VolumeTranslator::Stripe, stripe);
-
-    volume_create(volume, &volume_translators, &transport, &bricks, force)
+    GlusterClient::default().volume_create_striped(volume, stripe, transport, bricks, force)
 }
 
 /// This creates a new striped and replicated volume
 /// # Failures
-/// Will return GlusterError if the command fails to run
+/// Will return GlusterError::Unsupported on Gluster 6.0 and later or
+/// GlusterError if the command fails to run
 pub fn volume_create_striped_replicated(
     volume: &str,
     stripe: usize,
@@ -1397,11 +2580,49 @@ pub fn volume_create_striped_replicated(
     bricks: &[Brick],
     force: bool,
 ) -> Result<i32, GlusterError> {
-    let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
-    volume_translators.insert(VolumeTranslator::Stripe, stripe);
-    volume_translators.insert(VolumeTranslator::Replica, replica);
+    GlusterClient::default()
+        .volume_create_striped_replicated(volume, stripe, replica, transport, bricks, force)
+}
+
+#[test]
+fn test_volume_create_striped_unsupported() {
+    use executor::CannedExecutor;
+    use std::sync::Arc;
 
-    volume_create(volume, &volume_translators, &transport, &bricks, force)
+    let bricks: Vec<Brick> = vec![
+        "10.0.0.5:/mnt/sdb".parse().unwrap(),
+        "10.0.0.6:/mnt/sdb".parse().unwrap(),
+    ];
+    let canned = Arc::new(CannedExecutor::new().respond(&[], 0, "glusterfs 7.9\n", ""));
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    match client.volume_create_striped("test", 2, &Transport::Tcp, &bricks, false) {
+        Err(GlusterError::Unsupported { feature, .. }) => assert_eq!(feature, Feature::Stripe),
+        other => panic!("expected Unsupported, got {:?}", other),
+    }
+    // Nothing was created
+    assert_eq!(canned.calls().len(), 1);
+
+    let canned = Arc::new(CannedExecutor::new().respond(&[], 0, "glusterfs 5.13\n", ""));
+    let client = GlusterClient::builder().executor(canned.clone()).build();
+    client
+        .volume_create_striped("test", 2, &Transport::Tcp, &bricks, false)
+        .unwrap();
+    assert_eq!(
+        canned.calls()[1],
+        vec![
+            "gluster",
+            "--mode=script",
+            "volume",
+            "create",
+            "test",
+            "stripe",
+            "2",
+            "transport",
+            "tcp",
+            "10.0.0.5:/mnt/sdb",
+            "10.0.0.6:/mnt/sdb",
+        ]
+    );
 }
 
 /// This creates a new distributed volume
@@ -1413,9 +2634,7 @@ pub fn volume_create_distributed(
     bricks: &[Brick],
     force: bool,
 ) -> Result<i32, GlusterError> {
-    let volume_translators: HashMap<VolumeTranslator, String> = HashMap::new();
-
-    volume_create(volume, &volume_translators, &transport, &bricks, force)
+    GlusterClient::default().volume_create_distributed(volume, transport, bricks, force)
 }
 
 /// This creates a new erasure coded volume
@@ -1429,9 +2648,848 @@ pub fn volume_create_erasure(
     bricks: &[Brick],
     force: bool,
 ) -> Result<i32, GlusterError> {
-    let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
-    volume_translators.insert(VolumeTranslator::Disperse, disperse);
-    volume_translators.insert(VolumeTranslator::Redundancy, redundancy);
+    GlusterClient::default()
+        .volume_create_erasure(volume, disperse, redundancy, transport, bricks, force)
+}
+
+impl GlusterClient {
+    /// Lists all available volume names.
+    /// # Failures
+    /// Will return GlusterError if the Volume list command failed or if its
+    /// output could not be transformed into a String from utf8
+    pub fn volume_list(&self) -> Result<Vec<String>, GlusterError> {
+        let arg_list = vec!["volume", "list"];
+        let output = self.gluster_checked(&arg_list)?;
+        let output_str: String = String::from_utf8(output.stdout)?;
+        let mut volume_names: Vec<String> = Vec::new();
+        for line in output_str.lines() {
+            if line.is_empty() {
+                // Skip any blank lines in the output
+                continue;
+            }
+            if line.trim() == "No volumes present in cluster" {
+                break;
+            }
+            volume_names.push(line.trim().to_string());
+        }
+        Ok(volume_names)
+    }
+
+    /// Returns every Volume in the cluster with all available information.
+    /// This runs one `gluster volume info all --xml` and one peer listing no
+    /// matter how many volumes there are.
+    /// # Failures
+    /// Will return GlusterError if the command failed to run or its output
+    /// could not be parsed
+    pub fn volume_info_all(&self) -> Result<Vec<Volume>, GlusterError> {
+        let arg_list = vec!["volume", "info", "all", "--xml"];
+        let output = self.gluster_checked(&arg_list)?;
+        let output_str: String = String::from_utf8(output.stdout)?;
+        let volumes = parse_volume_info_xml(&output_str, &self.gluster_argv(&arg_list))?;
+        if volumes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let peers = self.peer_list()?;
+
+        volumes.into_iter().map(|v| v.into_volume(&peers)).collect()
+    }
+
+    /// Returns a Volume with all available information on the volume
+    /// # Failures
+    /// Will return GlusterError if the command failed to run.
+    pub fn volume_info(&self, volume: &str) -> Result<Volume, GlusterError> {
+        match self.volume_info_xml(volume) {
+            Ok(vol) => Ok(vol),
+            // Older Gluster versions and odd output fall back to scraping
+            // the human readable output
+            Err(GlusterError::XmlError(e)) => {
+                debug!("Unable to parse volume info xml: {}", e);
+                self.volume_info_text(volume)
+            }
+            Err(GlusterError::CommandFailed {
+                kind: CliFailure::Unknown,
+                ..
+            }) => {
+                debug!("Volume info --xml command failed");
+                self.volume_info_text(volume)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn volume_info_xml(&self, volume: &str) -> Result<Volume, GlusterError> {
+        let arg_list = vec!["volume", "info", volume, "--xml"];
+        let output = self.gluster_checked(&arg_list)?;
+        let output_str: String = String::from_utf8(output.stdout)?;
+        let vol = parse_volume_info_xml(&output_str, &self.gluster_argv(&arg_list))?
+            .pop()
+            .ok_or_else(|| GlusterError::VolumeNotFound(volume.to_string()))?;
+        let peers = self.peer_list()?;
+
+        vol.into_volume(&peers)
+    }
+
+    fn volume_info_text(&self, volume: &str) -> Result<Volume, GlusterError> {
+        let mut arg_list: Vec<String> = Vec::new();
+        arg_list.push("volume".to_string());
+        arg_list.push("info".to_string());
+        arg_list.push(volume.to_string());
+        let output = match self.gluster_checked(&arg_list) {
+            Ok(output) => output,
+            Err(e) => {
+                debug!("Volume info get command failed with error: {}", e);
+                return Err(e);
+            }
+        };
+        let output_str: String = String::from_utf8(output.stdout)?;
+
+        parse_volume_info(self, &volume, &output_str)
+    }
+
+    /// Returns a u64 representing the bytes used on the volume.
+    /// Note: This uses my brand new RPC library.  Some bugs may exist so use
+    /// caution.  This does not
+    /// shell out and therefore should be significantly faster.  It also suffers
+    /// far less hang conditions
+    /// than the CLI version.
+    /// # Failures
+    /// Will return GlusterError if the RPC fails
+    pub fn get_quota_usage(&self, volume: &str) -> Result<u64, GlusterError> {
+        let xid = 1; //Transaction ID number.
+        let prog = rpc::GLUSTER_QUOTA_PROGRAM_NUMBER;
+        let vers = 1; //RPC version == 1
+
+        let verf = rpc::GlusterAuth {
+            flavor: rpc::AuthFlavor::AuthNull,
+            stuff: vec![0, 0, 0, 0],
+        };
+        let verf_bytes = verf.pack()?;
+
+        let creds = rpc::GlusterCred {
+            flavor: rpc::GLUSTER_V2_CRED_FLAVOR,
+            pid: 0,
+            uid: 0,
+            gid: 0,
+            groups: "".to_string(),
+            lock_owner: vec![0, 0, 0, 0],
+        };
+        let cred_bytes = creds.pack()?;
+
+        let mut call_bytes = rpc::pack_quota_callheader(
+            xid,
+            prog,
+            vers,
+            rpc::GlusterAggregatorCommand::GlusterAggregatorGetlimit,
+            cred_bytes,
+            verf_bytes,
+        )?;
+
+        let mut dict: HashMap<String, Vec<u8>> = HashMap::with_capacity(4);
+
+        // TODO: Make a Gluster wd RPC call and parse this from the quota.conf file
+        // This is crap
+        let mut gfid = "00000000-0000-0000-0000-000000000001"
+            .to_string()
+            .into_bytes();
+        gfid.push(0); //Null Terminate
+        let mut name = volume.to_string().into_bytes();
+        name.push(0); //Null Terminate
+        let mut version = "1.20000005".to_string().into_bytes();
+        version.push(0); //Null Terminate
+                         //No idea what vol_type == 5 means to Gluster
+        let mut vol_type = "5".to_string().into_bytes();
+        vol_type.push(0); //Null Terminate
+
+        dict.insert("gfid".to_string(), gfid);
+        dict.insert("type".to_string(), vol_type);
+        dict.insert("volume-uuid".to_string(), name);
+        dict.insert("version".to_string(), version);
+        let quota_request = rpc::GlusterCliRequest { dict };
+        let quota_bytes = quota_request.pack()?;
+        for byte in quota_bytes {
+            call_bytes.push(byte);
+        }
+
+        let mut sock = UnixStream::connect(self.quotad_socket())?;
+
+        let _send_bytes = rpc::sendrecord(&mut sock, &call_bytes)?;
+        let mut reply_bytes = rpc::recvrecord(&mut sock)?;
+
+        let mut cursor = Cursor::new(&mut reply_bytes[..]);
+
+        // Check for success
+        rpc::unpack_replyheader(&mut cursor)?;
+
+        let mut cli_response = rpc::GlusterCliResponse::unpack(&mut cursor)?;
+        // The raw bytes
+        let quota_size_bytes = match cli_response.dict.get_mut("trusted.glusterfs.quota.size") {
+            Some(s) => s,
+            None => {
+                return Err(GlusterError::RpcFailed(
+                    "trusted.glusterfs.quota.size was not returned from \
+                     quotad"
+                        .to_string(),
+                ));
+            }
+        };
+        // Gluster is crazy and encodes a ton of data in this vector.  We're just going
+        // to
+        // read the first value and throw away the rest.  Why they didn't just use a
+        // struct and
+        // XDR is beyond me
+        let mut size_cursor = Cursor::new(&mut quota_size_bytes[..]);
+        let usage = size_cursor.read_u64::<BigEndian>()?;
+        Ok(usage)
+    }
+
+    /// Return a list of quotas on the volume if any.  The quota list command
+    /// is known to hang so consider limiting it with with_timeout().
+    /// # Failures
+    /// Will return GlusterError if the command failed to run.
+    pub fn quota_list(&self, volume: &str) -> Result<Vec<Quota>, GlusterError> {
+        let mut args_list: Vec<String> = Vec::new();
+        args_list.push("volume".to_string());
+        args_list.push("quota".to_string());
+        args_list.push(volume.to_string());
+        args_list.push("list".to_string());
+
+        let output = self.gluster_checked(&args_list)?;
+        let output_str: String = String::from_utf8(output.stdout)?;
+        let quota_list = parse_quota_list(volume, &output_str);
+
+        Ok(quota_list)
+    }
+
+    /// Enable bitrot detection and remediation on the volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_enable_bitrot(&self, volume: &str) -> Result<i32, GlusterError> {
+        let arg_list: Vec<&str> = vec!["volume", "bitrot", volume, "enable"];
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// Disable bitrot detection and remediation on the volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_disable_bitrot(&self, volume: &str) -> Result<i32, GlusterError> {
+        let arg_list: Vec<&str> = vec!["volume", "bitrot", volume, "disable"];
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// Set a bitrot option on the volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_set_bitrot_option(
+        &self,
+        volume: &str,
+        setting: &BitrotOption,
+    ) -> Result<i32, GlusterError> {
+        let arg_list: Vec<String> = vec![
+            "volume".to_string(),
+            "bitrot".to_string(),
+            volume.to_string(),
+            setting.to_string(),
+            setting.value(),
+        ];
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// Enable quotas on the volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_enable_quotas(&self, volume: &str) -> Result<i32, GlusterError> {
+        let arg_list: Vec<&str> = vec!["volume", "quota", volume, "enable"];
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// Check if quotas are already enabled on a volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_quotas_enabled(&self, volume: &str) -> Result<bool, GlusterError> {
+        let vol_info = self.volume_info(volume)?;
+        let quota = vol_info.options.get("features.quota");
+        match quota {
+            Some(v) => {
+                if v == "off" {
+                    Ok(false)
+                } else if v == "on" {
+                    Ok(true)
+                } else {
+                    // No idea what this is
+                    Ok(false)
+                }
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Disable quotas on the volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_disable_quotas(&self, volume: &str) -> Result<i32, GlusterError> {
+        let arg_list: Vec<&str> = vec!["volume", "quota", volume, "disable"];
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// Removes a size quota to the volume and path.
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_remove_quota(&self, volume: &str, path: &Path) -> Result<i32, GlusterError> {
+        let path_str = format!("{}", path.display());
+        let arg_list: Vec<&str> = vec!["volume", "quota", volume, "remove", &path_str];
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// Adds a size quota to the volume and path.
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_add_quota(
+        &self,
+        volume: &str,
+        path: &Path,
+        size: u64,
+    ) -> Result<i32, GlusterError> {
+        let path_str = format!("{}", path.display());
+        let size_string = size.to_string();
+        let arg_list: Vec<&str> = vec![
+            "volume",
+            "quota",
+            volume,
+            "limit-usage",
+            &path_str,
+            &size_string,
+        ];
+
+        self.gluster_mutate(&arg_list)?;
+
+        Ok(0)
+    }
+
+    /// Based on the replicas or erasure bits that are still available in the
+    /// volume this will return
+    /// True or False as to whether you can remove a Brick. This should be called
+    /// before volume_remove_brick()
+    pub fn ok_to_remove(&self, volume: &str, _brick: &Brick) -> Result<bool, GlusterError> {
+        // TODO: switch over to native RPC call to eliminate String regex parsing
+        let arg_list: Vec<&str> = vec!["vol", "status", volume];
+
+        let output = self.gluster_checked(&arg_list)?;
+
+        let output_str = String::from_utf8(output.stdout)?;
+        let _bricks = parse_volume_status(&output_str)?;
+        // The redudancy requirement is needed here.  The code needs to understand what
+        // volume type
+        // it's operating on.
+        Ok(true)
+    }
+
+    /// Query the status of the volume given.
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_status(&self, volume: &str) -> Result<VolumeStatus, GlusterError> {
+        let arg_list: Vec<&str> = vec!["vol", "status", volume];
+
+        let output = self.gluster_checked(&arg_list)?;
+
+        let output_str = String::from_utf8(output.stdout)?;
+        parse_volume_status(&output_str)
+    }
+
+    /// Query the disk usage and filesystem details of every brick in the
+    /// volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run or the volume
+    /// doesn't exist
+    pub fn volume_status_detail(&self, volume: &str) -> Result<Vec<BrickDetail>, GlusterError> {
+        let arg_list = ["volume", "status", volume, "detail", "--xml"];
+        let output = self.gluster_checked(&arg_list)?;
+        let output_str = String::from_utf8(output.stdout)?;
+        let mut details: Vec<BrickDetail> = Vec::new();
+        for vol in parse_volume_status_xml(&output_str, &self.gluster_argv(&arg_list))? {
+            for node in vol.node {
+                details.push(node.into_brick_detail()?);
+            }
+        }
+        Ok(details)
+    }
+
+    /// This will remove a brick from the volume.  Without force this starts
+    /// migrating the brick's data off it, with force the brick is removed
+    /// straight away.
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_remove_brick(
+        &self,
+        volume: &str,
+        bricks: Vec<Brick>,
+        force: bool,
+    ) -> Result<i32, GlusterError> {
+        if bricks.is_empty() {
+            return Err(GlusterError::InvalidArgument(
+                "The brick list is empty. Not shrinking volume".to_string(),
+            ));
+        }
 
-    volume_create(volume, &volume_translators, &transport, &bricks, force)
+        for brick in bricks {
+            let ok = self.ok_to_remove(&volume, &brick)?;
+            if ok {
+                // volume remove-brick <VOLNAME> <BRICK> <start|force>
+                let arg_list: Vec<String> = vec![
+                    "volume".to_string(),
+                    "remove-brick".to_string(),
+                    volume.to_string(),
+                    brick.to_string(),
+                    if force { "force" } else { "start" }.to_string(),
+                ];
+                self.gluster_mutate(&arg_list)?;
+            } else {
+                return Err(GlusterError::InvalidArgument(
+                    "Unable to remove brick due to redundancy failure".to_string(),
+                ));
+            }
+        }
+        Ok(0)
+    }
+
+    // volume add-brick <VOLNAME> [<stripe|replica> <COUNT>]
+    // <NEW-BRICK> ... [force] - add brick to volume <VOLNAME>
+    /// This adds a new brick to the volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_add_brick(
+        &self,
+        volume: &str,
+        bricks: &[Brick],
+        force: bool,
+    ) -> Result<i32, GlusterError> {
+        if bricks.is_empty() {
+            return Err(GlusterError::InvalidArgument(
+                "The brick list is empty. Not expanding volume".to_string(),
+            ));
+        }
+
+        let mut arg_list: Vec<String> = Vec::new();
+        arg_list.push("volume".to_string());
+        arg_list.push("add-brick".to_string());
+        arg_list.push(volume.to_string());
+
+        for brick in bricks.iter() {
+            arg_list.push(brick.to_string());
+        }
+        if force {
+            arg_list.push("force".to_string());
+        }
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// Once a volume is created it needs to be started.  This starts the volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_start(&self, volume: &str, force: bool) -> Result<i32, GlusterError> {
+        // Should I check the volume exists first?
+        let mut arg_list: Vec<&str> = vec!["volume", "start", volume];
+
+        if force {
+            arg_list.push("force");
+        }
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// This stops a running volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_stop(&self, volume: &str, force: bool) -> Result<i32, GlusterError> {
+        let mut arg_list: Vec<&str> = vec!["volume", "stop", volume];
+
+        if force {
+            arg_list.push("force");
+        }
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// This deletes a stopped volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_delete(&self, volume: &str) -> Result<i32, GlusterError> {
+        let arg_list: Vec<&str> = vec!["volume", "delete", volume];
+
+        self.gluster_mutate(&arg_list)?;
+
+        Ok(0)
+    }
+
+    fn volume_create<T: ToString>(
+        &self,
+        volume: &str,
+        options: &HashMap<VolumeTranslator, T>,
+        transport: &Transport,
+        bricks: &[Brick],
+        force: bool,
+    ) -> Result<i32, GlusterError> {
+        if bricks.is_empty() {
+            return Err(GlusterError::InvalidArgument(
+                "The brick list is empty. Not creating volume".to_string(),
+            ));
+        }
+
+        // TODO: figure out how to check each VolumeTranslator type
+        // if (bricks.len() % replica_count) != 0 {
+        // return Err("The brick list and replica count are not multiples. Not creating
+        // volume".to_string());
+        // }
+        //
+
+        let mut arg_list: Vec<String> = Vec::new();
+        arg_list.push("volume".to_string());
+        arg_list.push("create".to_string());
+        arg_list.push(volume.to_string());
+
+        for (key, value) in options.iter() {
+            arg_list.push(key.clone().to_string());
+            arg_list.push(value.to_string());
+        }
+
+        arg_list.push("transport".to_string());
+        arg_list.push(transport.clone().to_string());
+
+        for brick in bricks.iter() {
+            arg_list.push(brick.to_string());
+        }
+        if force {
+            arg_list.push("force".to_string());
+        }
+        self.gluster_mutate(&arg_list)?;
+        Ok(0)
+    }
+
+    /// Every (key, value) pair `gluster volume get` lists for key, which
+    /// may be "all".  volume may be "all" for cluster wide options.
+    pub(crate) fn vol_get(
+        &self,
+        volume: &str,
+        key: &str,
+    ) -> Result<Vec<(String, String)>, GlusterError> {
+        let arg_list = ["volume", "get", volume, key, "--xml"];
+        let output = self.gluster_checked(&arg_list)?;
+        parse_volume_get_xml(
+            &String::from_utf8(output.stdout)?,
+            &self.gluster_argv(&arg_list),
+        )
+    }
+
+    // The keys listed under "Options Reconfigured"
+    fn reconfigured_keys(&self, volume: &str) -> Result<Vec<String>, GlusterError> {
+        let arg_list = ["volume", "info", volume, "--xml"];
+        let output = self.gluster_checked(&arg_list)?;
+        let vol = parse_volume_info_xml(
+            &String::from_utf8(output.stdout)?,
+            &self.gluster_argv(&arg_list),
+        )?
+        .pop()
+        .ok_or_else(|| GlusterError::VolumeNotFound(volume.to_string()))?;
+        Ok(vol
+            .options
+            .option
+            .into_iter()
+            .map(|o| o.name.trim().to_string())
+            .collect())
+    }
+
+    /// Every option the volume has with the value in effect, defaults
+    /// included.  Volume info only lists the options that were set.
+    /// # Failures
+    /// Will return GlusterError if the command fails to run or the volume
+    /// doesn't exist
+    pub fn volume_get_options(&self, volume: &str) -> Result<Vec<VolumeOption>, GlusterError> {
+        let options = self.vol_get(volume, "all")?;
+        let reconfigured = self.reconfigured_keys(volume)?;
+        Ok(options
+            .into_iter()
+            .map(|(key, value)| volume_option(key, value, &reconfigured))
+            .collect())
+    }
+
+    /// The value in effect for one volume option.  Gluster accepts short
+    /// keys like "readdir-ahead" and the full key is returned.
+    /// # Failures
+    /// Will return GlusterError if the command fails to run, the volume
+    /// doesn't exist or there is no such option
+    pub fn volume_get_option(&self, volume: &str, key: &str) -> Result<VolumeOption, GlusterError> {
+        let (key, value) = self
+            .vol_get(volume, key)?
+            .pop()
+            .ok_or_else(|| GlusterError::parse_failure(key, "volume get"))?;
+        let reconfigured = self.reconfigured_keys(volume)?;
+        Ok(volume_option(key, value, &reconfigured))
+    }
+
+    /// Put a volume option back to its default.  key "all" resets every
+    /// option.  force is needed to reset some protected options.
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_reset_option(
+        &self,
+        volume: &str,
+        key: &str,
+        force: bool,
+    ) -> Result<(), GlusterError> {
+        let mut arg_list: Vec<&str> = vec!["volume", "reset", volume, key];
+        if force {
+            arg_list.push("force");
+        }
+        self.gluster_mutate(&arg_list)?;
+        Ok(())
+    }
+
+    fn vol_set(&self, volume: &str, option: &GlusterOption) -> Result<i32, GlusterError> {
+        let mut arg_list: Vec<String> = Vec::new();
+        arg_list.push("volume".to_string());
+        arg_list.push("set".to_string());
+        arg_list.push(volume.to_string());
+
+        arg_list.push(option.key());
+        arg_list.push(option.value());
+
+        self.gluster_mutate(&arg_list)?;
+
+        Ok(0)
+    }
+
+    /// Set an option on the volume.  Every value is validated before any
+    /// of them is set.  Setting stops at the first option that fails and
+    /// the ones set before it stay set; use volume_apply_options to have
+    /// them put back.
+    /// # Failures
+    /// Will return GlusterError::InvalidArgument if a value is invalid or
+    /// the error of the first option that couldn't be set
+    pub fn volume_set_options(
+        &self,
+        volume: &str,
+        settings: &[GlusterOption],
+    ) -> Result<i32, GlusterError> {
+        validate_all(settings)?;
+        for gluster_opt in settings {
+            self.vol_set(volume, gluster_opt)?;
+        }
+        Ok(0)
+    }
+
+    /// Set several options on the volume so that either all of them are set
+    /// or, as far as possible, none are.  The current values of the
+    /// affected keys are read first and the options are set in order.  If
+    /// one fails the ones already set are put back, last first: reset if
+    /// they had the default before, otherwise set to their old value.  The
+    /// failed option is put back too unless glusterd rejected it before
+    /// committing, as it may be set on some peers after a timeout.
+    ///
+    /// **A failed apply is not an error.**  Failures to set or restore an
+    /// option are in the report, so check ApplyReport::is_success().
+    /// # Failures
+    /// Will return GlusterError::InvalidArgument if a value is invalid or
+    /// GlusterError if the current values can't be read.  Nothing has been
+    /// set in either case.
+    pub fn volume_apply_options(
+        &self,
+        volume: &str,
+        settings: &[GlusterOption],
+    ) -> Result<ApplyReport, GlusterError> {
+        validate_all(settings)?;
+
+        // Look each key up as given so short keys like "readdir-ahead" are
+        // matched to the full key glusterd reports
+        let reconfigured = self.reconfigured_keys(volume)?;
+        let mut snapshot: HashMap<String, Restore> = HashMap::new();
+        for gluster_opt in settings {
+            let key = gluster_opt.key();
+            if snapshot.contains_key(&key) {
+                continue;
+            }
+            let (full_key, value) = self
+                .vol_get(volume, &key)?
+                .pop()
+                .ok_or_else(|| GlusterError::parse_failure(&key, "volume get"))?;
+            let current = volume_option(full_key, value, &reconfigured);
+            let restore = match current.source {
+                OptionSource::Reconfigured => Restore::Set(
+                    GlusterOption::from_str(&current.key, current.value.clone())
+                        .unwrap_or(GlusterOption::Unknown(current.key, current.value)),
+                ),
+                OptionSource::Default => Restore::Reset(current.key),
+            };
+            snapshot.insert(key, restore);
+        }
+
+        let mut report = ApplyReport {
+            applied: Vec::new(),
+            failed: None,
+            rolled_back: Vec::new(),
+            rollback_failed: Vec::new(),
+        };
+        for gluster_opt in settings {
+            match self.vol_set(volume, gluster_opt) {
+                Ok(_) => report.applied.push(gluster_opt.clone()),
+                Err(e) => {
+                    report.failed = Some((gluster_opt.clone(), e));
+                    break;
+                }
+            }
+        }
+        let mut to_restore: Vec<&GlusterOption> = match report.failed {
+            None => return Ok(report),
+            // A timeout or a failed commit may have left the option set on
+            // some peers
+            Some((ref gluster_opt, ref e)) if !is_rejected(e) => vec![gluster_opt],
+            Some(_) => Vec::new(),
+        };
+        to_restore.extend(report.applied.iter().rev());
+
+        let mut restored: Vec<String> = Vec::new();
+        let mut rolled_back = Vec::new();
+        let mut rollback_failed = Vec::new();
+        for gluster_opt in to_restore {
+            let key = gluster_opt.key();
+            if restored.contains(&key) {
+                continue;
+            }
+            let restore = snapshot[&key].clone();
+            let result = match restore {
+                Restore::Set(ref previous) => self.vol_set(volume, previous).map(|_| ()),
+                Restore::Reset(ref key) => self.volume_reset_option(volume, key, false),
+            };
+            match result {
+                Ok(()) => rolled_back.push(restore),
+                Err(e) => {
+                    warn!("Failed to restore {} on {}: {}", key, volume, e);
+                    rollback_failed.push((restore, e));
+                }
+            }
+            restored.push(key);
+        }
+        report.rolled_back = rolled_back;
+        report.rollback_failed = rollback_failed;
+        Ok(report)
+    }
+
+    /// This creates a new replicated volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_create_replicated(
+        &self,
+        volume: &str,
+        replica_count: usize,
+        transport: &Transport,
+        bricks: &[Brick],
+        force: bool,
+    ) -> Result<i32, GlusterError> {
+        let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
+        volume_translators.insert(VolumeTranslator::Replica, replica_count);
+
+        self.volume_create(volume, &volume_translators, &transport, &bricks, force)
+    }
+
+    /// The arbiter volume is special subset of replica volumes that is aimed at preventing
+    /// split-brains and providing the same consistency guarantees as a normal replica 3 volume
+    /// without consuming 3x space.
+    /// # Failures
+    /// Will return GlusterError::Unsupported before Gluster 3.7 or
+    /// GlusterError if the command fails to run
+    pub fn volume_create_arbiter(
+        &self,
+        volume: &str,
+        replica_count: usize,
+        arbiter_count: usize,
+        transport: &Transport,
+        bricks: &[Brick],
+        force: bool,
+    ) -> Result<i32, GlusterError> {
+        self.require(Feature::Arbiter)?;
+        let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
+        volume_translators.insert(VolumeTranslator::Replica, replica_count);
+        volume_translators.insert(VolumeTranslator::Arbiter, arbiter_count);
+
+        self.volume_create(volume, &volume_translators, &transport, &bricks, force)
+    }
+
+    /// This creates a new striped volume
+    /// # Failures
+    /// Will return GlusterError::Unsupported on Gluster 6.0 and later or
+    /// GlusterError if the command fails to run
+    pub fn volume_create_striped(
+        &self,
+        volume: &str,
+        stripe: usize,
+        transport: &Transport,
+        bricks: &[Brick],
+        force: bool,
+    ) -> Result<i32, GlusterError> {
+        self.require(Feature::Stripe)?;
+        let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
+        volume_translators.insert(VolumeTranslator::Stripe, stripe);
+
+        self.volume_create(volume, &volume_translators, &transport, &bricks, force)
+    }
+
+    /// This creates a new striped and replicated volume
+    /// # Failures
+    /// Will return GlusterError::Unsupported on Gluster 6.0 and later or
+    /// GlusterError if the command fails to run
+    pub fn volume_create_striped_replicated(
+        &self,
+        volume: &str,
+        stripe: usize,
+        replica: usize,
+        transport: &Transport,
+        bricks: &[Brick],
+        force: bool,
+    ) -> Result<i32, GlusterError> {
+        self.require(Feature::Stripe)?;
+        let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
+        volume_translators.insert(VolumeTranslator::Stripe, stripe);
+        volume_translators.insert(VolumeTranslator::Replica, replica);
+
+        self.volume_create(volume, &volume_translators, &transport, &bricks, force)
+    }
+
+    /// This creates a new distributed volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_create_distributed(
+        &self,
+        volume: &str,
+        transport: &Transport,
+        bricks: &[Brick],
+        force: bool,
+    ) -> Result<i32, GlusterError> {
+        let volume_translators: HashMap<VolumeTranslator, String> = HashMap::new();
+
+        self.volume_create(volume, &volume_translators, &transport, &bricks, force)
+    }
+
+    /// This creates a new erasure coded volume
+    /// # Failures
+    /// Will return GlusterError if the command fails to run
+    pub fn volume_create_erasure(
+        &self,
+        volume: &str,
+        disperse: usize,
+        redundancy: usize,
+        transport: &Transport,
+        bricks: &[Brick],
+        force: bool,
+    ) -> Result<i32, GlusterError> {
+        let mut volume_translators: HashMap<VolumeTranslator, usize> = HashMap::new();
+        volume_translators.insert(VolumeTranslator::Disperse, disperse);
+        volume_translators.insert(VolumeTranslator::Redundancy, redundancy);
+
+        self.volume_create(volume, &volume_translators, &transport, &bricks, force)
+    }
 }
@@ -57,4 +57,89 @@ fn test_read_fop_stats() {
 fn test_get_local_ip() {
     let result = gluster::get_local_ip();
     println!("local ip: {:?}", result);
+    let result = gluster::get_local_ips().unwrap();
+    println!("local ips: {:?}", result);
+    assert!(result.contains(&"127.0.0.1".parse().unwrap()));
+}
+
+#[test]
+fn test_classify_cli_failures() {
+    let host = |h: &str| h.to_string();
+    let cases = vec![
+        ("transaction_in_progress", CliFailure::TransactionInProgress),
+        (
+            "transaction_could_be_in_progress",
+            CliFailure::TransactionInProgress,
+        ),
+        (
+            "locking_failed",
+            CliFailure::LockingFailed {
+                host: host("172.31.19.130"),
+            },
+        ),
+        (
+            "brick_in_use",
+            CliFailure::BrickInUse {
+                brick: "/mnt/brick1/test".to_string(),
+            },
+        ),
+        (
+            "brick_in_use_add_brick",
+            CliFailure::BrickInUse {
+                brick: "ip-172-31-19-130:/bricks/brick-1".to_string(),
+            },
+        ),
+        (
+            "staging_failed",
+            CliFailure::StagingFailed {
+                host: host("gluster-2.example.com"),
+                reason: "Volume test does not exist".to_string(),
+            },
+        ),
+        (
+            "staging_failed_multiple",
+            CliFailure::StagingFailed {
+                host: host("172.31.19.131"),
+                reason: "Please check log file for details.".to_string(),
+            },
+        ),
+        (
+            "commit_failed",
+            CliFailure::CommitFailed {
+                host: host("172.31.19.131"),
+                reason: "Please check log file for details.".to_string(),
+            },
+        ),
+        (
+            "peer_not_connected",
+            CliFailure::PeerNotConnected {
+                host: host("gluster-3"),
+            },
+        ),
+        (
+            "host_not_connected",
+            CliFailure::PeerNotConnected {
+                host: host("172.31.19.133"),
+            },
+        ),
+        ("quorum_not_met", CliFailure::QuorumNotMet),
+        ("server_quorum_is_not_met", CliFailure::QuorumNotMet),
+        (
+            "volume_not_found",
+            CliFailure::VolumeNotFound {
+                volume: "missing".to_string(),
+            },
+        ),
+        ("unknown", CliFailure::Unknown),
+    ];
+
+    for (fixture, expected) in cases {
+        let message = {
+            let mut f = File::open(format!("tests/cli_failures/{}.txt", fixture)).unwrap();
+            let mut s = String::new();
+            f.read_to_string(&mut s).unwrap();
+            s
+        };
+        assert_eq!(CliFailure::classify(&message), expected, "{}", fixture);
+    }
 }